@@ -0,0 +1,269 @@
+//! `#[derive(CommandParser)]` for command enums, generating a parser which accepts each variant as
+//! its token followed by its fields separated by spaces.
+//!
+//! ```ignore
+//! #[derive(CommandParser)]
+//! enum Command {
+//!     #[command(desc = "bid on the current lot")]
+//!     Bid(#[command(parser = "Int::bounded(1, 10)", name = "amount")] i32),
+//!     #[command(token = "give", desc = "give cards to a player")]
+//!     Give {
+//!         #[command(parser = "Player {}")]
+//!         player: usize,
+//!         #[command(parser = "Int::positive()", opt)]
+//!         times: Option<i32>,
+//!     },
+//!     Pass,
+//! }
+//! ```
+//!
+//! This generates `CommandParser`, a unit struct implementing `Parser<Command>`. Variant options:
+//!
+//! * `token`, the word which starts the command, defaulting to the lowercase variant name.
+//! * `desc`, documents the command, wrapping it in a `Doc` named after the token.
+//!
+//! Field options:
+//!
+//! * `parser`, required, an expression for the field's parser. Names in it are resolved where the
+//!   enum is defined, so parsers used need to be imported there.
+//! * `name`, wraps the parser in a `Doc` with the name.
+//! * `opt`, makes the field optional, so the field type must be an `Option`.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Error, Expr, Field, Fields, Lit, LitStr,
+    Meta, NestedMeta, Variant,
+};
+
+// Chains go up to `Chain12` and the token takes one place.
+const MAX_FIELDS: usize = 11;
+
+#[proc_macro_derive(CommandParser, attributes(command))]
+pub fn derive_command_parser(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let data = match input.data {
+        Data::Enum(ref data) => data,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "CommandParser can only be derived for enums",
+            ))
+        }
+    };
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "CommandParser can't be derived for generic enums",
+        ));
+    }
+    let name = &input.ident;
+    let vis = &input.vis;
+    let parser_name = format_ident!("{}Parser", name);
+    let doc = format!("Parses `{}`, generated by `#[derive(CommandParser)]`.", name);
+    let variants = data
+        .variants
+        .iter()
+        .map(|v| variant_parser(name, v))
+        .collect::<syn::Result<Vec<TokenStream2>>>()?;
+    // Paths are absolute so the code still compiles where names like `Result` are redefined.
+    Ok(quote! {
+        #[doc = #doc]
+        #vis struct #parser_name;
+
+        impl #parser_name {
+            /// Run `f` with the parser, which is only built once for each thread.
+            fn with_parser<R, F>(f: F) -> R
+            where
+                F: ::std::ops::FnOnce(
+                    &::brdgme_game::command::parser::OneOf<
+                        #name,
+                        dyn ::brdgme_game::command::parser::Parser<#name>,
+                    >,
+                ) -> R,
+            {
+                ::std::thread_local! {
+                    static PARSER: ::brdgme_game::command::parser::OneOf<
+                        #name,
+                        dyn ::brdgme_game::command::parser::Parser<#name>,
+                    > = ::brdgme_game::command::parser::OneOf::new(::std::vec![#(
+                        ::std::boxed::Box::new(#variants)
+                            as ::std::boxed::Box<
+                                dyn ::brdgme_game::command::parser::Parser<#name>
+                            >
+                    ),*]);
+                }
+                PARSER.with(f)
+            }
+        }
+
+        impl ::brdgme_game::command::parser::Parser<#name> for #parser_name {
+            fn parse<'a>(
+                &self,
+                input: &'a str,
+                names: &[::std::string::String],
+            ) -> ::std::result::Result<
+                ::brdgme_game::command::parser::Output<'a, #name>,
+                ::brdgme_game::errors::GameError,
+            > {
+                Self::with_parser(|parser| parser.parse(input, names))
+            }
+
+            fn expected(
+                &self,
+                names: &[::std::string::String],
+            ) -> ::std::vec::Vec<::std::string::String> {
+                Self::with_parser(|parser| parser.expected(names))
+            }
+
+            fn to_spec(&self) -> ::brdgme_game::command::Spec {
+                Self::with_parser(|parser| parser.to_spec())
+            }
+        }
+    })
+}
+
+fn variant_parser(name: &syn::Ident, variant: &Variant) -> syn::Result<TokenStream2> {
+    let mut token = None;
+    let mut desc = None;
+    for meta in command_metas(&variant.attrs)? {
+        if meta.path().is_ident("token") {
+            token = Some(string_value(&meta)?);
+        } else if meta.path().is_ident("desc") {
+            desc = Some(string_value(&meta)?);
+        } else {
+            return Err(Error::new_spanned(meta, "unknown variant option"));
+        }
+    }
+    let token = token
+        .map(|t| t.value())
+        .unwrap_or_else(|| variant.ident.to_string().to_lowercase());
+    let fields = variant.fields.iter().collect::<Vec<&Field>>();
+    if fields.len() > MAX_FIELDS {
+        return Err(Error::new_spanned(
+            &variant.ident,
+            format!("commands can have at most {} fields", MAX_FIELDS),
+        ));
+    }
+    let field_parsers = fields
+        .iter()
+        .map(|f| field_parser(f))
+        .collect::<syn::Result<Vec<TokenStream2>>>()?;
+    let bindings = (0..fields.len())
+        .map(|i| format_ident!("field_{}", i))
+        .collect::<Vec<syn::Ident>>();
+    let variant_name = &variant.ident;
+    let construct = match variant.fields {
+        Fields::Named(..) => {
+            let idents = fields.iter().map(|f| &f.ident);
+            quote!(#name::#variant_name { #(#idents: #bindings),* })
+        }
+        Fields::Unnamed(..) => quote!(#name::#variant_name(#(#bindings),*)),
+        Fields::Unit => quote!(#name::#variant_name),
+    };
+    let parser = if fields.is_empty() {
+        quote! {
+            ::brdgme_game::command::parser::Map::new(
+                ::brdgme_game::command::parser::Token::new(#token),
+                |_| #construct,
+            )
+        }
+    } else {
+        let chain = format_ident!("Chain{}", fields.len() + 1);
+        quote! {
+            ::brdgme_game::command::parser::Map::new(
+                ::brdgme_game::command::parser::#chain::new(
+                    ::brdgme_game::command::parser::Token::new(#token),
+                    #(#field_parsers),*
+                ),
+                |(_, #(#bindings),*)| #construct,
+            )
+        }
+    };
+    Ok(match desc {
+        Some(desc) => quote! {
+            ::brdgme_game::command::parser::Doc::name_desc(#token, #desc, #parser)
+        },
+        None => parser,
+    })
+}
+
+fn field_parser(field: &Field) -> syn::Result<TokenStream2> {
+    let mut parser = None;
+    let mut name = None;
+    let mut opt = false;
+    for meta in command_metas(&field.attrs)? {
+        if meta.path().is_ident("parser") {
+            parser = Some(string_value(&meta)?.parse::<Expr>()?);
+        } else if meta.path().is_ident("name") {
+            name = Some(string_value(&meta)?);
+        } else if meta.path().is_ident("opt") {
+            if let Meta::Path(..) = meta {
+                opt = true;
+            } else {
+                return Err(Error::new_spanned(meta, "expected `opt` without a value"));
+            }
+        } else {
+            return Err(Error::new_spanned(meta, "unknown field option"));
+        }
+    }
+    let parser = match parser {
+        Some(parser) => quote!(#parser),
+        None => {
+            return Err(Error::new_spanned(
+                field,
+                "fields need a parser, such as #[command(parser = \"Int::any()\")]",
+            ))
+        }
+    };
+    let parser = match name {
+        Some(name) => quote!(::brdgme_game::command::parser::Doc::name(#name, #parser)),
+        None => parser,
+    };
+    let parser = quote!(::brdgme_game::command::parser::AfterSpace::new(#parser));
+    Ok(if opt {
+        quote!(::brdgme_game::command::parser::Opt::new(#parser))
+    } else {
+        parser
+    })
+}
+
+/// The options inside every `#[command(...)]` attribute.
+fn command_metas(attrs: &[Attribute]) -> syn::Result<Vec<Meta>> {
+    let mut metas = vec![];
+    for attr in attrs.iter().filter(|a| a.path.is_ident("command")) {
+        match attr.parse_meta()? {
+            Meta::List(list) => {
+                for nested in list.nested {
+                    match nested {
+                        NestedMeta::Meta(meta) => metas.push(meta),
+                        NestedMeta::Lit(lit) => {
+                            return Err(Error::new_spanned(lit, "expected a command option"))
+                        }
+                    }
+                }
+            }
+            meta => return Err(Error::new_spanned(meta, "expected #[command(...)]")),
+        }
+    }
+    Ok(metas)
+}
+
+fn string_value(meta: &Meta) -> syn::Result<LitStr> {
+    match *meta {
+        Meta::NameValue(ref name_value) => match name_value.lit {
+            Lit::Str(ref s) => Ok(s.to_owned()),
+            ref lit => Err(Error::new_spanned(lit, "expected a string")),
+        },
+        _ => Err(Error::new_spanned(meta, "expected a string value")),
+    }
+}
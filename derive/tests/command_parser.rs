@@ -0,0 +1,122 @@
+use brdgme_game::command::parser::*;
+use brdgme_game_derive::CommandParser;
+
+#[derive(Debug, PartialEq, CommandParser)]
+enum Command {
+    #[command(desc = "bid on the current lot")]
+    Bid(#[command(parser = "Int::bounded(1, 10)", name = "amount")] i32),
+    #[command(token = "give", desc = "give cards to a player")]
+    Give {
+        #[command(parser = "Player {}")]
+        player: usize,
+        #[command(parser = "Many::some(Enum::partial(vec![\"cheese\", \"bacon\"]))")]
+        cards: Vec<&'static str>,
+        #[command(parser = "Int::positive()", opt)]
+        times: Option<i32>,
+    },
+    Pass,
+}
+
+/// Common names are redefined here to check the generated code doesn't depend on the prelude.
+#[allow(dead_code, unused_macros)]
+mod shadowed {
+    use brdgme_game::command::parser::*;
+    use brdgme_game_derive::CommandParser;
+
+    type Result<T> = std::result::Result<T, ()>;
+    struct Box;
+    struct String;
+    struct Vec;
+    macro_rules! vec {
+        () => {};
+    }
+
+    #[derive(Debug, PartialEq, CommandParser)]
+    pub enum Command {
+        Take(#[command(parser = "Int::positive()")] i32),
+        Pass,
+    }
+}
+
+#[test]
+fn derived_parser_uses_absolute_paths() {
+    let output = shadowed::CommandParser
+        .parse("take 2", &names())
+        .expect("expected 'take 2' to parse");
+    assert_eq!(shadowed::Command::Take(2), output.value);
+}
+
+fn names() -> Vec<String> {
+    vec!["mick".to_string(), "steve".to_string()]
+}
+
+fn parse(input: &str) -> Command {
+    let output = CommandParser
+        .parse(input, &names())
+        .unwrap_or_else(|e| panic!("expected '{}' to parse: {}", input, e));
+    assert_eq!("", output.remaining, "for input '{}'", input);
+    output.value
+}
+
+#[test]
+fn derived_parser_works() {
+    assert_eq!(Command::Bid(5), parse("bid 5"));
+    assert_eq!(Command::Pass, parse("PASS"));
+    assert_eq!(
+        Command::Give {
+            player: 1,
+            cards: vec!["bacon", "cheese"],
+            times: Some(2),
+        },
+        parse("give st b, ch 2")
+    );
+    assert_eq!(
+        Command::Give {
+            player: 0,
+            cards: vec!["cheese"],
+            times: None,
+        },
+        parse("give mick cheese")
+    );
+    CommandParser
+        .parse("bid 11", &names())
+        .expect_err("expected 'bid 11' to produce an error");
+}
+
+#[test]
+fn derived_spec_matches_hand_written() {
+    let parsers: Vec<Box<dyn Parser<Command>>> = vec![
+        Box::new(Doc::name_desc(
+            "bid",
+            "bid on the current lot",
+            Map::new(
+                Chain2::new(
+                    Token::new("bid"),
+                    AfterSpace::new(Doc::name("amount", Int::bounded(1, 10))),
+                ),
+                |(_, amount)| Command::Bid(amount),
+            ),
+        )),
+        Box::new(Doc::name_desc(
+            "give",
+            "give cards to a player",
+            Map::new(
+                Chain4::new(
+                    Token::new("give"),
+                    AfterSpace::new(Player {}),
+                    AfterSpace::new(Many::some(Enum::partial(vec!["cheese", "bacon"]))),
+                    Opt::new(AfterSpace::new(Int::positive())),
+                ),
+                |(_, player, cards, times)| Command::Give {
+                    player,
+                    cards,
+                    times,
+                },
+            ),
+        )),
+        Box::new(Map::new(Token::new("pass"), |_| Command::Pass)),
+    ];
+    let hand_written = OneOf::new(parsers);
+    assert_eq!(hand_written.to_spec(), CommandParser.to_spec());
+    assert_eq!(hand_written.expected(&names()), CommandParser.expected(&names()));
+}
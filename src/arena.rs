@@ -0,0 +1,353 @@
+//! Play bots against each other to compare their strength.
+
+use rand::Rng;
+use serde_derive::{Deserialize, Serialize};
+use serde_json;
+
+use std::fmt;
+
+use crate::bot::{player_names, Botter};
+use crate::errors::GameError;
+use crate::game::{GameRng, Gamer};
+
+/// Games which take more commands than this are abandoned, which stops bots which only produce
+/// invalid input from playing forever.
+const MAX_COMMANDS_DEFAULT: usize = 10_000;
+// Stream for `GameRng::sub_seed` used to pick who moves, the game itself uses the seed directly.
+const TURN_STREAM: u64 = 1;
+
+/// Plays seeded games between a set of bots at every player count the game supports. Seats are
+/// rotated between games so no bot benefits from always going first.
+pub struct Arena<G: Gamer> {
+    bots: Vec<(String, Box<dyn Botter<G>>)>,
+    games: usize,
+    seed: u64,
+    max_commands: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GameResult {
+    pub seed: u64,
+    /// The index of the bot in each seat.
+    pub seats: Vec<usize>,
+    /// Empty if the game was abandoned.
+    pub placings: Vec<usize>,
+    pub points: Vec<f32>,
+    pub invalid_inputs: usize,
+}
+
+impl GameResult {
+    pub fn finished(&self) -> bool {
+        !self.placings.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BotSummary {
+    pub name: String,
+    /// The number of seats the bot filled in finished games.
+    pub games: usize,
+    /// Seats which finished first, including shared first places.
+    pub wins: usize,
+    pub win_rate: f32,
+    pub avg_placing: f32,
+    /// `None` if the game doesn't report points.
+    pub avg_points: Option<f32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ArenaResults {
+    pub bots: Vec<BotSummary>,
+    pub games: Vec<GameResult>,
+    pub abandoned: usize,
+}
+
+impl<G: Gamer> Arena<G> {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            bots: vec![],
+            games: 1,
+            seed,
+            max_commands: MAX_COMMANDS_DEFAULT,
+        }
+    }
+
+    pub fn bot<B, N>(mut self, name: N, bot: B) -> Self
+    where
+        B: Botter<G> + 'static,
+        N: Into<String>,
+    {
+        self.bots.push((name.into(), Box::new(bot)));
+        self
+    }
+
+    /// The number of games to play at each player count.
+    pub fn games(mut self, games: usize) -> Self {
+        self.games = games;
+        self
+    }
+
+    pub fn max_commands(mut self, max_commands: usize) -> Self {
+        self.max_commands = max_commands;
+        self
+    }
+
+    pub fn run(&mut self) -> Result<ArenaResults, GameError> {
+        if self.bots.is_empty() {
+            return Err(GameError::internal("no bots in the arena"));
+        }
+        let mut rng = GameRng::from_seed(self.seed);
+        let mut games: Vec<GameResult> = vec![];
+        for player_count in G::player_counts() {
+            for game in 0..self.games {
+                let seats = (0..player_count)
+                    .map(|seat| (game + seat) % self.bots.len())
+                    .collect::<Vec<usize>>();
+                games.push(self.play(rng.gen(), seats)?);
+            }
+        }
+        Ok(ArenaResults {
+            bots: self.summarise(&games),
+            abandoned: games.iter().filter(|g| !g.finished()).count(),
+            games,
+        })
+    }
+
+    fn play(&mut self, seed: u64, seats: Vec<usize>) -> Result<GameResult, GameError> {
+        let mut rng = GameRng::from_seed(GameRng::sub_seed(seed, TURN_STREAM));
+        let players = player_names(seats.len());
+        let (mut game, _) = G::new_with_options(seats.len(), &G::Options::default(), seed)?;
+        let mut invalid_inputs = 0;
+        let mut command_count = 0;
+        while !game.is_finished() && command_count < self.max_commands {
+            let player = *rng
+                .choose(&game.whose_turn())
+                .ok_or_else(|| GameError::internal("it is nobody's turn"))?;
+            let spec = game
+                .command_spec(player)
+                .ok_or_else(|| GameError::internal("expected a command spec"))?;
+            let bot_commands = self.bots[seats[player]].1.commands(
+                player,
+                &game.player_state(player),
+                &players,
+                &spec,
+                Some(format!("{}", seed)),
+            );
+            // Bots are trusted to rank their own commands, so the best is always played.
+            let best = match bot_commands
+                .iter()
+                .enumerate()
+                .max_by_key(|&(i, c)| (c.quality, -(i as isize)))
+            {
+                Some((_, best)) => best,
+                // A bot with nothing to play abandons the game.
+                None => break,
+            };
+            for command in &best.commands {
+                command_count += 1;
+                match game.command(player, command, &players) {
+                    Ok(..) => {}
+                    Err(e @ GameError::Internal { .. }) => return Err(e),
+                    // Any other error is the bot's fault, so it only counts against its seat.
+                    Err(..) => {
+                        invalid_inputs += 1;
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(GameResult {
+            seed,
+            placings: game.placings(),
+            points: game.points(),
+            seats,
+            invalid_inputs,
+        })
+    }
+
+    fn summarise(&self, games: &[GameResult]) -> Vec<BotSummary> {
+        self.bots
+            .iter()
+            .enumerate()
+            .map(|(bot, (name, _))| {
+                let seats = games
+                    .iter()
+                    .filter(|g| g.finished())
+                    .flat_map(|g| {
+                        g.seats
+                            .iter()
+                            .enumerate()
+                            .filter(|&(_, &b)| b == bot)
+                            .map(move |(seat, _)| (g, seat))
+                    })
+                    .collect::<Vec<(&GameResult, usize)>>();
+                let points = seats
+                    .iter()
+                    .filter_map(|&(g, seat)| g.points.get(seat).cloned())
+                    .collect::<Vec<f32>>();
+                let wins = seats.iter().filter(|&&(g, seat)| g.placings[seat] == 1).count();
+                BotSummary {
+                    name: name.to_owned(),
+                    games: seats.len(),
+                    wins,
+                    win_rate: ratio(wins as f32, seats.len()),
+                    avg_placing: ratio(
+                        seats.iter().map(|&(g, seat)| g.placings[seat] as f32).sum(),
+                        seats.len(),
+                    ),
+                    avg_points: if points.is_empty() {
+                        None
+                    } else {
+                        Some(ratio(points.iter().sum(), points.len()))
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+fn ratio(total: f32, count: usize) -> f32 {
+    if count == 0 {
+        0.0
+    } else {
+        total / count as f32
+    }
+}
+
+impl ArenaResults {
+    pub fn to_json(&self) -> Result<String, GameError> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| GameError::internal(format!("unable to serialize results: {}", e)))
+    }
+}
+
+/// Renders the bot summaries as a table.
+impl fmt::Display for ArenaResults {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name_width = self.bots
+            .iter()
+            .map(|b| b.name.chars().count())
+            .chain(Some(3))
+            .max()
+            .unwrap_or(0);
+        writeln!(
+            f,
+            "{:<w$}  {:>6}  {:>6}  {:>8}  {:>9}  {:>10}",
+            "Bot",
+            "Games",
+            "Wins",
+            "Win rate",
+            "Avg place",
+            "Avg points",
+            w = name_width
+        )?;
+        for bot in &self.bots {
+            writeln!(
+                f,
+                "{:<w$}  {:>6}  {:>6}  {:>7.1}%  {:>9.2}  {:>10}",
+                bot.name,
+                bot.games,
+                bot.wins,
+                bot.win_rate * 100.0,
+                bot.avg_placing,
+                bot.avg_points
+                    .map(|p| format!("{:.2}", p))
+                    .unwrap_or_else(|| "-".to_string()),
+                w = name_width
+            )?;
+        }
+        if self.abandoned > 0 {
+            writeln!(f, "{} of {} games abandoned", self.abandoned, self.games.len())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::{BotCommand, SpecBot, BOT_STREAM};
+    use crate::command::Spec as CommandSpec;
+    use crate::test_game::{Race, RaceState, TARGET};
+
+    /// Finishes the race whenever it can, otherwise adds 1.
+    struct Finisher;
+
+    impl Botter<Race> for Finisher {
+        fn commands(
+            &mut self,
+            _player: usize,
+            player_state: &RaceState,
+            _players: &[String],
+            _command_spec: &CommandSpec,
+            _game_id: Option<String>,
+        ) -> Vec<BotCommand> {
+            let needed = TARGET - player_state.total;
+            vec![format!("add {}", if needed <= 3 { needed } else { 1 }).into()]
+        }
+    }
+
+    fn run(seed: u64) -> ArenaResults {
+        Arena::<Race>::new(seed)
+            .bot(
+                "random",
+                SpecBot::from_rng(GameRng::from_seed(GameRng::sub_seed(seed, BOT_STREAM))),
+            )
+            .bot("finisher", Finisher)
+            .games(20)
+            .run()
+            .expect("expected arena to run")
+    }
+
+    /// Plays a fixed list of commands, whatever the game.
+    struct Fixed(Vec<&'static str>);
+
+    impl Botter<Race> for Fixed {
+        fn commands(
+            &mut self,
+            _player: usize,
+            _player_state: &RaceState,
+            _players: &[String],
+            _command_spec: &CommandSpec,
+            _game_id: Option<String>,
+        ) -> Vec<BotCommand> {
+            self.0.iter().map(|&c| c.into()).collect()
+        }
+    }
+
+    #[test]
+    fn arena_survives_bad_bots() {
+        let results = Arena::<Race>::new(1)
+            .bot("silent", Fixed(vec![]))
+            .bot("broken", Fixed(vec!["add 9"]))
+            .games(2)
+            .max_commands(50)
+            .run()
+            .expect("expected arena to run");
+        assert_eq!(results.games.len(), results.abandoned);
+        assert!(results.games.iter().any(|g| g.invalid_inputs > 0));
+    }
+
+    #[test]
+    fn arena_works() {
+        let results = run(42);
+        assert_eq!(60, results.games.len());
+        assert_eq!(0, results.abandoned);
+        // Every seat is filled by one of the bots.
+        assert_eq!(
+            results.games.iter().map(|g| g.seats.len()).sum::<usize>(),
+            results.bots.iter().map(|b| b.games).sum::<usize>()
+        );
+        assert!(results.bots[1].win_rate > results.bots[0].win_rate);
+        assert!(results.bots[0].avg_points.is_some());
+        assert_eq!(results, run(42));
+        let table = results.to_string();
+        assert!(table.contains("finisher"));
+        assert!(table.contains("Win rate"));
+        let json = results.to_json().expect("expected results to serialize");
+        assert_eq!(
+            results,
+            serde_json::from_str(&json).expect("expected results to deserialize")
+        );
+    }
+}
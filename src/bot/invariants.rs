@@ -0,0 +1,94 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json;
+
+use crate::game::{Gamer, Status};
+
+/// Check the parts of the `Gamer` contract which apply to every game, returning a description of
+/// the first violation found.
+pub fn check<G: Gamer>(game: &G) -> Result<(), String> {
+    let player_count = game.player_count();
+    match game.status() {
+        Status::Active {
+            whose_turn,
+            eliminated,
+        } => {
+            if whose_turn.is_empty() {
+                return Err("game is active but it is nobody's turn".to_string());
+            }
+            for &p in &whose_turn {
+                if p >= player_count {
+                    return Err(format!(
+                        "it is player {}'s turn but there are only {} players",
+                        p,
+                        player_count
+                    ));
+                }
+                if eliminated.contains(&p) {
+                    return Err(format!("it is player {}'s turn but they are eliminated", p));
+                }
+                if game.command_spec(p).is_none() {
+                    return Err(format!(
+                        "it is player {}'s turn but there is no command spec for them",
+                        p
+                    ));
+                }
+            }
+        }
+        Status::Finished { placings, stats } => {
+            if placings.len() != player_count {
+                return Err(format!(
+                    "game is finished with {} placings for {} players",
+                    placings.len(),
+                    player_count
+                ));
+            }
+            if stats.len() != player_count {
+                return Err(format!(
+                    "game is finished with {} stats for {} players",
+                    stats.len(),
+                    player_count
+                ));
+            }
+        }
+    }
+    round_trip(&game.pub_state()).map_err(|e| format!("pub state {}", e))?;
+    for p in 0..player_count {
+        round_trip(&game.player_state(p)).map_err(|e| format!("player {} state {}", p, e))?;
+    }
+    Ok(())
+}
+
+/// Ensure that serializing a value, deserializing it and serializing it again gives the same JSON.
+fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> Result<(), String> {
+    let json = serde_json::to_value(value).map_err(|e| format!("failed to serialize: {}", e))?;
+    let deserialized: T = serde_json::from_value(json.clone())
+        .map_err(|e| format!("failed to deserialize: {}", e))?;
+    let reserialized = serde_json::to_value(&deserialized)
+        .map_err(|e| format!("failed to serialize after deserializing: {}", e))?;
+    if json != reserialized {
+        return Err(format!(
+            "changed after deserializing, from {} to {}",
+            json,
+            reserialized
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_game::Race;
+
+    #[test]
+    fn check_works() {
+        let (mut game, _) = Race::new(3).expect("expected game");
+        check(&game).expect("expected new game to pass");
+        game.current = 3;
+        check(&game).expect_err("expected out of range player to fail");
+        game.current = 0;
+        game.winner = Some(1);
+        check(&game).expect("expected finished game to pass");
+    }
+}
@@ -0,0 +1,400 @@
+use rand::Rng;
+
+use std::collections::BTreeSet;
+use std::time::{Duration, Instant};
+
+use crate::bot::{spec_bot, BotCommand, Botter};
+use crate::command::Spec as CommandSpec;
+use crate::game::{
+    enumerate_moves, validate_by_cloning, GameRng, Gamer, MoveEnumerator, Status,
+};
+
+const ITERATIONS_DEFAULT: usize = 1000;
+const EXPLORATION_DEFAULT: f64 = 1.4;
+const MAX_PLAYOUT_DEFAULT: usize = 1000;
+// How many random inputs are generated from the command spec when looking for moves.
+const SAMPLES: usize = 50;
+// How many random inputs a playout tries for a player before giving up on the playout.
+const PLAYOUT_TRIES: usize = 10;
+
+/// Finds the moves available to a player, the game is always a clone so it is safe to run
+/// commands on it.
+pub type MoveGen<G> = Box<dyn Fn(&G, usize, &[String], &mut GameRng) -> Vec<String>>;
+
+/// Builds a game to search from what a player can see, sampling any hidden information.
+pub type Determinize<G> = Box<dyn Fn(&<G as Gamer>::PlayerState, &mut GameRng) -> G>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Budget {
+    Iterations(usize),
+    Time(Duration),
+}
+
+/// A Monte Carlo tree search bot which works for any game that can be cloned. Moves are found by
+/// enumerating the command spec, or sampling it if it is too large, unless a move generator is
+/// given with `moves`. Playouts play random inputs from the command spec without a move
+/// generator, as enumerating every step is too slow. Each playout is scored by placings, or by
+/// points if it doesn't finish.
+///
+/// Playing as a `Botter` only gives the bot the player state, so it must be turned into a
+/// `DeterminizedMctsBot` with `determinize` first.
+pub struct MctsBot<G: Gamer + Clone> {
+    rng: GameRng,
+    budget: Budget,
+    exploration: f64,
+    max_playout: usize,
+    moves: Option<MoveGen<G>>,
+}
+
+/// An `MctsBot` which builds the game to search from the player state, so it can play as a
+/// `Botter`.
+pub struct DeterminizedMctsBot<G: Gamer + Clone> {
+    bot: MctsBot<G>,
+    determinize: Determinize<G>,
+}
+
+struct Node<G> {
+    game: G,
+    player: Option<usize>,
+    untried: Vec<String>,
+    children: Vec<(String, usize)>,
+    parent: Option<usize>,
+    visits: f64,
+    rewards: Vec<f64>,
+}
+
+impl<G: Gamer + Clone + 'static> MctsBot<G> {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: GameRng::from_seed(seed),
+            budget: Budget::Iterations(ITERATIONS_DEFAULT),
+            exploration: EXPLORATION_DEFAULT,
+            max_playout: MAX_PLAYOUT_DEFAULT,
+            moves: None,
+        }
+    }
+
+    /// Create a bot which uses the game's own list of legal moves.
+    pub fn enumerated(seed: u64) -> Self
+    where
+        G: MoveEnumerator,
+    {
+        Self::new(seed).moves(|game: &G, player, players: &[String], _: &mut GameRng| {
+            game.legal_moves(player, players)
+        })
+    }
+
+    pub fn budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// The UCT exploration constant, higher values try less promising moves more often.
+    pub fn exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// Playouts which take more commands than this are scored where they stop.
+    pub fn max_playout(mut self, max_playout: usize) -> Self {
+        self.max_playout = max_playout;
+        self
+    }
+
+    pub fn moves<F>(mut self, moves: F) -> Self
+    where
+        F: Fn(&G, usize, &[String], &mut GameRng) -> Vec<String> + 'static,
+    {
+        self.moves = Some(Box::new(moves));
+        self
+    }
+
+    /// Build the game to search from the player state, so the bot can play as a `Botter`.
+    /// Hidden information must be sampled rather than copied from the real game, otherwise the
+    /// bot cheats.
+    pub fn determinize<F>(self, determinize: F) -> DeterminizedMctsBot<G>
+    where
+        F: Fn(&G::PlayerState, &mut GameRng) -> G + 'static,
+    {
+        DeterminizedMctsBot {
+            bot: self,
+            determinize: Box::new(determinize),
+        }
+    }
+
+    /// Search from `game` for moves for `player`, returning every move tried with the most
+    /// promising first. Quality is the share of the search spent on each move.
+    pub fn search(&mut self, game: &G, player: usize, players: &[String]) -> Vec<BotCommand> {
+        let mut nodes = vec![Node {
+            game: game.clone(),
+            player: Some(player),
+            untried: self.tree_moves(game, player, players),
+            children: vec![],
+            parent: None,
+            visits: 0.0,
+            rewards: vec![0.0; game.player_count()],
+        }];
+        let start = Instant::now();
+        let mut iterations = 0;
+        while match self.budget {
+            Budget::Iterations(max) => iterations < max,
+            Budget::Time(max) => start.elapsed() < max,
+        } {
+            iterations += 1;
+            let leaf = self.select(&nodes);
+            let leaf = self.expand(&mut nodes, leaf, players);
+            let rewards = self.playout(&nodes[leaf].game, players);
+            let mut current = Some(leaf);
+            while let Some(n) = current {
+                nodes[n].visits += 1.0;
+                for (total, reward) in nodes[n].rewards.iter_mut().zip(&rewards) {
+                    *total += reward;
+                }
+                current = nodes[n].parent;
+            }
+        }
+        let mut children = nodes[0].children.clone();
+        children.sort_by(|a, b| nodes[b.1].visits.partial_cmp(&nodes[a.1].visits).unwrap());
+        let total = nodes[0].visits.max(1.0);
+        children
+            .into_iter()
+            .map(|(command, child)| BotCommand {
+                quality: (nodes[child].visits / total * 255.0).round() as u8,
+                commands: vec![command],
+            })
+            .collect()
+    }
+
+    /// Walk down fully expanded nodes picking the child with the best UCT score.
+    fn select(&self, nodes: &[Node<G>]) -> usize {
+        let mut current = 0;
+        loop {
+            let node = &nodes[current];
+            let player = match node.player {
+                Some(player) if node.untried.is_empty() && !node.children.is_empty() => player,
+                _ => return current,
+            };
+            let log_visits = node.visits.ln();
+            current = node.children
+                .iter()
+                .map(|&(_, c)| {
+                    let child = &nodes[c];
+                    let score = child.rewards[player] / child.visits
+                        + self.exploration * (log_visits / child.visits).sqrt();
+                    (c, score)
+                })
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+                .map(|(c, _)| c)
+                .unwrap();
+        }
+    }
+
+    /// Add a child for an untried move, returning the node to play out from.
+    fn expand(&mut self, nodes: &mut Vec<Node<G>>, parent: usize, players: &[String]) -> usize {
+        let player = match nodes[parent].player {
+            Some(player) => player,
+            None => return parent,
+        };
+        while !nodes[parent].untried.is_empty() {
+            let index = self.rng.gen_range(0, nodes[parent].untried.len());
+            let command = nodes[parent].untried.swap_remove(index);
+            let mut game = nodes[parent].game.clone();
+            if game.command(player, &command, players).is_err() {
+                continue;
+            }
+            let next_player = self.rng.choose(&game.whose_turn()).cloned();
+            let untried = match next_player {
+                Some(p) => self.tree_moves(&game, p, players),
+                None => vec![],
+            };
+            let child = nodes.len();
+            nodes.push(Node {
+                rewards: vec![0.0; game.player_count()],
+                game,
+                player: next_player,
+                untried,
+                children: vec![],
+                parent: Some(parent),
+                visits: 0.0,
+            });
+            nodes[parent].children.push((command, child));
+            return child;
+        }
+        parent
+    }
+
+    fn playout(&mut self, game: &G, players: &[String]) -> Vec<f64> {
+        let mut game = game.clone();
+        for _ in 0..self.max_playout {
+            let player = match self.rng.choose(&game.whose_turn()) {
+                Some(&player) => player,
+                None => break,
+            };
+            let played = match self.moves {
+                Some(ref moves) => {
+                    let moves = moves(&game, player, players, &mut self.rng);
+                    match self.rng.choose(&moves) {
+                        Some(command) => game.command(player, command, players).is_ok(),
+                        None => false,
+                    }
+                }
+                None => play_random(&mut game, player, players, &mut self.rng),
+            };
+            if !played {
+                break;
+            }
+        }
+        rewards(&game)
+    }
+
+    /// The moves to try when expanding the tree.
+    fn tree_moves(&mut self, game: &G, player: usize, players: &[String]) -> Vec<String> {
+        match self.moves {
+            Some(ref moves) => moves(game, player, players, &mut self.rng),
+            None => spec_moves(game, player, players, &mut self.rng),
+        }
+    }
+}
+
+impl<G: Gamer + Clone + 'static> Botter<G> for DeterminizedMctsBot<G> {
+    fn commands(
+        &mut self,
+        player: usize,
+        player_state: &G::PlayerState,
+        players: &[String],
+        _command_spec: &CommandSpec,
+        _game_id: Option<String>,
+    ) -> Vec<BotCommand> {
+        let game = (self.determinize)(player_state, &mut self.bot.rng);
+        self.bot.search(&game, player, players)
+    }
+}
+
+/// Each player's share of the pairwise wins against the other players, using placings if the
+/// game is finished and points otherwise.
+fn rewards<G: Gamer>(game: &G) -> Vec<f64> {
+    let player_count = game.player_count();
+    let (scores, higher_better): (Vec<f64>, bool) = match game.status() {
+        Status::Finished { placings, .. } => {
+            (placings.into_iter().map(|p| p as f64).collect(), false)
+        }
+        Status::Active { .. } => (game.points().into_iter().map(f64::from).collect(), true),
+    };
+    if player_count < 2 || scores.len() != player_count {
+        return vec![0.5; player_count];
+    }
+    (0..player_count)
+        .map(|p| {
+            (0..player_count)
+                .filter(|&opp| opp != p)
+                .map(|opp| {
+                    if scores[p] == scores[opp] {
+                        0.5
+                    } else if (scores[p] > scores[opp]) == higher_better {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                })
+                .sum::<f64>() / (player_count - 1) as f64
+        })
+        .collect()
+}
+
+/// Play a random input from the command spec, trying a few before giving up. Failed commands
+/// don't change the game, so they are run directly instead of checked on a clone.
+fn play_random<G: Gamer>(
+    game: &mut G,
+    player: usize,
+    players: &[String],
+    rng: &mut GameRng,
+) -> bool {
+    let spec = match game.command_spec(player) {
+        Some(spec) => spec,
+        None => return false,
+    };
+    (0..PLAYOUT_TRIES).any(|_| {
+        let command = spec_bot::generate(&spec, players, rng);
+        game.command(player, &command, players).is_ok()
+    })
+}
+
+fn spec_moves<G: Gamer + Clone>(
+    game: &G,
+    player: usize,
+    players: &[String],
+    rng: &mut GameRng,
+) -> Vec<String> {
+    enumerate_moves(game, player, players)
+        .unwrap_or_else(|| sampled_moves(game, player, players, rng))
+}
+
+/// Sample inputs from the command spec, keeping the distinct ones the game accepts.
+fn sampled_moves<G: Gamer + Clone>(
+    game: &G,
+    player: usize,
+    players: &[String],
+    rng: &mut GameRng,
+) -> Vec<String> {
+    let spec = match game.command_spec(player) {
+        Some(spec) => spec,
+        None => return vec![],
+    };
+    (0..SAMPLES)
+        .map(|_| spec_bot::generate(&spec, players, rng))
+        .collect::<BTreeSet<String>>()
+        .into_iter()
+        .filter(|command| accepts(game, player, command, players))
+        .collect()
+}
+
+fn accepts<G: Gamer + Clone>(game: &G, player: usize, command: &str, players: &[String]) -> bool {
+    validate_by_cloning(game, player, command, players)
+        .map(|response| response.remaining_input.trim().is_empty())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::player_names;
+    use crate::test_game::{Race, RaceState};
+
+    #[test]
+    fn mcts_bot_works() {
+        let players = player_names(2);
+        let (mut game, _) = Race::new(2).expect("expected game to start");
+        game.total = 7;
+        let mut bot = MctsBot::new(1);
+        let commands = bot.search(&game, 0, &players);
+        assert_eq!(3, commands.len());
+        assert_eq!(vec!["add 3".to_string()], commands[0].commands);
+        // From 5 the only move which doesn't let the opponent reach 10 is to add 1.
+        game.total = 5;
+        let commands = MctsBot::new(1)
+            .budget(Budget::Iterations(3000))
+            .search(&game, 0, &players);
+        assert_eq!(vec!["add 1".to_string()], commands[0].commands);
+        let commands = MctsBot::enumerated(1).search(&game, 0, &players);
+        assert_eq!(3, commands.len());
+    }
+
+    #[test]
+    fn mcts_bot_determinizes_player_state() {
+        let players = player_names(2);
+        let state = RaceState {
+            total: 7,
+            current: 0,
+        };
+        let (game, _) = Race::new(2).expect("expected game to start");
+        let spec = game.command_spec(0).expect("expected a command spec");
+        let mut bot = MctsBot::new(1).determinize(|state: &RaceState, _: &mut GameRng| {
+            let (mut game, _) = Race::new(2).expect("expected game to start");
+            game.total = state.total;
+            game.current = state.current;
+            game
+        });
+        let commands = bot.commands(0, &state, &players, &spec, None);
+        assert_eq!(vec!["add 3".to_string()], commands[0].commands);
+    }
+}
@@ -0,0 +1,404 @@
+use rand::{self, Rng};
+use chrono;
+use serde_derive::{Serialize, Deserialize};
+use ::log::{log, trace};
+
+use std::any::Any;
+use std::fmt;
+use std::io::Write;
+use std::panic::{self, AssertUnwindSafe};
+
+pub mod invariants;
+pub mod mcts;
+pub mod shrink;
+pub mod spec_bot;
+
+use crate::game::{GameRng, Gamer};
+use crate::command::Spec as CommandSpec;
+use crate::errors::GameError;
+pub use self::mcts::{Budget, DeterminizedMctsBot, MctsBot};
+pub use self::shrink::{shrink, Shrunk};
+pub use self::spec_bot::SpecBot;
+
+const BOT_COMMAND_QUALITY_DEFAULT: u8 = 128;
+// Streams for `GameRng::sub_seed`, the game itself uses the seed directly.
+const FUZZER_STREAM: u64 = 1;
+/// The `GameRng::sub_seed` stream for seeding a bot from a game's seed, so the bot doesn't draw
+/// the same numbers as the game.
+pub const BOT_STREAM: u64 = 2;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct BotCommand {
+    pub quality: u8,
+    pub commands: Vec<String>,
+}
+
+impl Default for BotCommand {
+    fn default() -> Self {
+        BotCommand {
+            quality: BOT_COMMAND_QUALITY_DEFAULT,
+            commands: vec![],
+        }
+    }
+}
+
+impl<I> From<I> for BotCommand
+where
+    I: Into<String>,
+{
+    fn from(s: I) -> Self {
+        BotCommand {
+            commands: vec![s.into()],
+            ..BotCommand::default()
+        }
+    }
+}
+
+pub trait Botter<T: Gamer> {
+    fn commands(
+        &mut self,
+        player: usize,
+        player_state: &T::PlayerState,
+        players: &[String],
+        command_spec: &CommandSpec,
+        game_id: Option<String>,
+    ) -> Vec<BotCommand>;
+}
+
+/// A record of a failed fuzz game, containing everything needed to replay it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FuzzFailure {
+    pub seed: u64,
+    pub player_count: usize,
+    pub commands: Vec<(usize, String)>,
+    pub error: String,
+}
+
+impl fmt::Display for FuzzFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "fuzzing failed for seed {} with {} players: {}",
+            self.seed,
+            self.player_count,
+            self.error
+        )?;
+        writeln!(f, "commands:")?;
+        for &(player, ref command) in &self.commands {
+            writeln!(f, "    ({}, {:?}),", player, command)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Fuzzer<G: Gamer, B: Botter<G>> {
+    game: Option<G>,
+    player_counts: Vec<usize>,
+    player_names: Vec<String>,
+    player_count: usize,
+    bot: B,
+    new_bot: Option<Box<dyn Fn(u64) -> B>>,
+    seed: u64,
+    next_seed: u64,
+    rng: GameRng,
+    commands: Vec<(usize, String)>,
+    game_count: usize,
+    command_count: usize,
+    invalid_input_count: usize,
+}
+
+impl<G: Gamer, B: Botter<G>> Fuzzer<G, B> {
+    pub fn new(bot: B) -> Self {
+        Self::with_seed(bot, rand::thread_rng().gen())
+    }
+
+    /// Create a fuzzer whose first game is driven by `seed`, which is passed to
+    /// `Gamer::new_with_options` along with the default options. The fuzzer's own choices are
+    /// drawn from a seed derived from it. Each following game is seeded from the one before it.
+    ///
+    /// The bot keeps its state between games, so the seed reported for a failing game only
+    /// reproduces it if the bot is stateless. The reported command history always reproduces it
+    /// with `replay`, or use `with_bot_factory` so the seed reproduces the whole run.
+    pub fn with_seed(bot: B, seed: u64) -> Self {
+        let player_counts = G::player_counts();
+        Self {
+            game: None,
+            player_names: player_names(player_counts.iter().max().cloned().unwrap_or(0)),
+            player_counts,
+            player_count: 0,
+            bot,
+            new_bot: None,
+            seed,
+            next_seed: seed,
+            rng: GameRng::from_seed(GameRng::sub_seed(seed, FUZZER_STREAM)),
+            commands: vec![],
+            game_count: 0,
+            command_count: 0,
+            invalid_input_count: 0,
+        }
+    }
+
+    /// Create a fuzzer like `with_seed`, but with a fresh bot for every game, created from a seed
+    /// derived from the game's seed. A seeded bot makes the seed reported for a failing game
+    /// reproduce it.
+    pub fn with_bot_factory<F>(new_bot: F, seed: u64) -> Self
+    where
+        F: Fn(u64) -> B + 'static,
+    {
+        let bot = new_bot(GameRng::sub_seed(seed, BOT_STREAM));
+        Self {
+            new_bot: Some(Box::new(new_bot)),
+            ..Self::with_seed(bot, seed)
+        }
+    }
+
+    /// The seed of the current game.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn status(&self) -> String {
+        format!(
+            "Games: {}\tCommands: {}\tInvalid inputs: {}",
+            self.game_count,
+            self.command_count,
+            self.invalid_input_count
+        )
+    }
+
+    pub fn fuzz<O>(&mut self, out: &mut O)
+    where
+        O: Write,
+    {
+        let mut last_status = chrono::Utc::now().timestamp();
+        loop {
+            self.next();
+            let now = chrono::Utc::now().timestamp();
+            if now - last_status > 1 {
+                last_status = now;
+                writeln!(out, "{}", self.status()).unwrap();
+            }
+        }
+    }
+
+    fn new_game(&mut self) -> Result<(), String> {
+        self.game_count += 1;
+        self.seed = self.next_seed;
+        self.rng = GameRng::from_seed(GameRng::sub_seed(self.seed, FUZZER_STREAM));
+        self.next_seed = self.rng.gen();
+        if let Some(ref new_bot) = self.new_bot {
+            self.bot = new_bot(GameRng::sub_seed(self.seed, BOT_STREAM));
+        }
+        self.commands = vec![];
+        self.game = None;
+        self.player_count = *self.rng
+            .choose(&self.player_counts)
+            .ok_or("no player counts for game type")?;
+        self.game = Some(
+            G::new_with_options(self.player_count, &G::Options::default(), self.seed)
+                .map_err(|e| format!("failed to create new game: {:?}", e))?
+                .0,
+        );
+        Ok(())
+    }
+
+    fn step(&mut self) -> Result<(), String> {
+        let game = match self.game {
+            Some(ref mut game) => game,
+            None => return Err("no game to step".to_string()),
+        };
+        let player = *self.rng
+            .choose(&game.whose_turn())
+            .ok_or("is nobody's turn")?;
+        let players = &self.player_names[..self.player_count];
+        let player_state = game.player_state(player);
+        let command_spec = game.command_spec(player).ok_or("expected a command spec")?;
+        let bot_commands = self.bot.commands(
+            player,
+            &player_state,
+            players,
+            &command_spec,
+            Some(format!("{}", self.game_count)),
+        );
+        let input = self.rng
+            .choose(&bot_commands)
+            .ok_or("bot returned no commands")?;
+        let cmd = input
+            .commands
+            .first()
+            .ok_or("BotCommand with no commands was returned by bot")?;
+        self.commands.push((player, cmd.to_owned()));
+        let cmd_res = game.command(player, cmd, players);
+        self.command_count += 1;
+        match cmd_res {
+            Ok(..) => Ok(()),
+            Err(GameError::InvalidInput { message }) => {
+                self.invalid_input_count += 1;
+                trace!("invalid input '{}' for player {}: {}", cmd, player, message);
+                Ok(())
+            }
+            _ => Err(format!(
+                "error running command '{}' for player {}, {:?}",
+                cmd,
+                player,
+                cmd_res
+            )),
+        }
+    }
+
+    fn check(&self) -> Result<(), String> {
+        match self.game {
+            Some(ref game) => {
+                invariants::check(game).map_err(|e| format!("invariant violated: {}", e))
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn failure(&self, error: String) -> FuzzFailure {
+        FuzzFailure {
+            seed: self.seed,
+            player_count: self.player_count,
+            commands: self.commands.clone(),
+            error,
+        }
+    }
+}
+
+impl<G: Gamer, B: Botter<G>> Iterator for Fuzzer<G, B> {
+    type Item = ();
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            if self.game.as_ref().map(|g| g.is_finished()).unwrap_or(true) {
+                self.new_game()?;
+            } else {
+                self.step()?;
+            }
+            self.check()
+        }));
+        let error = match result {
+            Ok(Ok(())) => return Some(()),
+            Ok(Err(message)) => message,
+            Err(payload) => format!("panicked: {}", panic_message(&payload)),
+        };
+        let failure = self.failure(error);
+        match shrink::<G>(failure.player_count, failure.seed, &failure.commands) {
+            Some(shrunk) => panic!(
+                "{}shrunk to {} commands:\n{}",
+                failure,
+                shrunk.commands.len(),
+                shrunk
+            ),
+            None => panic!("{}", failure),
+        }
+    }
+}
+
+/// The player names used by the fuzzer, which are just the player numbers.
+pub fn player_names(player_count: usize) -> Vec<String> {
+    (0..player_count).map(|c| format!("{}", c)).collect()
+}
+
+/// Replay a command history from a `FuzzFailure` against a new game created with the seed. Invalid
+/// input is skipped as it is when fuzzing, any other error is returned. Invariant violations are
+/// returned as internal errors.
+pub fn replay<G: Gamer>(
+    player_count: usize,
+    seed: u64,
+    commands: &[(usize, String)],
+) -> Result<G, GameError> {
+    let players = player_names(player_count);
+    let (mut game, _) = G::new_with_options(player_count, &G::Options::default(), seed)?;
+    check_replay(&game)?;
+    for &(player, ref command) in commands {
+        match game.command(player, command, &players) {
+            Ok(..) | Err(GameError::InvalidInput { .. }) => {}
+            Err(e) => return Err(e),
+        }
+        check_replay(&game)?;
+    }
+    Ok(game)
+}
+
+fn check_replay<G: Gamer>(game: &G) -> Result<(), GameError> {
+    invariants::check(game).map_err(|e| GameError::internal(format!("invariant violated: {}", e)))
+}
+
+pub(crate) fn panic_message(payload: &Box<dyn Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.to_owned()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_game::{BrokenRace, Race, BROKEN_AT};
+
+    fn seeded_fuzzer<G: Gamer>(seed: u64) -> Fuzzer<G, SpecBot<GameRng>> {
+        Fuzzer::with_bot_factory(|seed| SpecBot::from_rng(GameRng::from_seed(seed)), seed)
+    }
+
+    fn fuzz_until_failure<G: Gamer>(fuzzer: &mut Fuzzer<G, SpecBot<GameRng>>) -> String {
+        let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+            for _ in 0..10_000 {
+                fuzzer.next();
+            }
+        })).expect_err("expected fuzzing to fail");
+        panic_message(&payload)
+    }
+
+    #[test]
+    fn fuzzer_is_deterministic() {
+        let mut a = seeded_fuzzer::<Race>(42);
+        let mut b = seeded_fuzzer::<Race>(42);
+        for _ in 0..500 {
+            a.next();
+            b.next();
+            assert_eq!(a.seed, b.seed);
+            assert_eq!(a.player_count, b.player_count);
+            assert_eq!(a.commands, b.commands);
+        }
+    }
+
+    #[test]
+    fn fuzzer_reports_failure() {
+        let mut fuzzer = seeded_fuzzer::<BrokenRace>(42);
+        let message = fuzz_until_failure(&mut fuzzer);
+        assert!(
+            message.starts_with(&format!("fuzzing failed for seed {}", fuzzer.seed)),
+            "unexpected failure message: {}",
+            message
+        );
+        match replay::<BrokenRace>(fuzzer.player_count, fuzzer.seed, &fuzzer.commands) {
+            Err(GameError::Internal { .. }) => {}
+            _ => panic!("expected replay to reproduce the failure"),
+        }
+        // A fuzzer started from the reported seed fails in its first game with the same history.
+        let mut reproduced = seeded_fuzzer::<BrokenRace>(fuzzer.seed);
+        fuzz_until_failure(&mut reproduced);
+        assert_eq!(1, reproduced.game_count);
+        assert_eq!(fuzzer.commands, reproduced.commands);
+    }
+
+    #[test]
+    fn replay_works() {
+        let commands = vec![
+            (0, "add 3".to_string()),
+            (1, "add 5".to_string()),
+            (1, "add 3".to_string()),
+        ];
+        let game = replay::<Race>(2, 0, &commands).expect("expected replay to succeed");
+        assert_eq!(6, game.total);
+        let commands = [commands, vec![(0, "add 1".to_string())]].concat();
+        match replay::<BrokenRace>(2, 0, &commands) {
+            Err(GameError::Internal { .. }) => {}
+            _ => panic!("expected total of {} to fail", BROKEN_AT),
+        }
+    }
+}
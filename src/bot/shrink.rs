@@ -0,0 +1,204 @@
+use std::any;
+use std::cmp;
+use std::fmt;
+use std::mem::{self, Discriminant};
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::bot::{invariants, panic_message, player_names};
+use crate::errors::GameError;
+use crate::game::Gamer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FailureKind {
+    Error(Discriminant<GameError>),
+    Invariant,
+    Panic,
+}
+
+struct Failure {
+    kind: FailureKind,
+    message: String,
+    // The number of commands run up to and including the failure.
+    len: usize,
+}
+
+/// A minimal command history which reproduces a failure.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shrunk {
+    pub game_type: String,
+    pub player_count: usize,
+    pub seed: u64,
+    pub commands: Vec<(usize, String)>,
+    pub error: String,
+}
+
+impl fmt::Display for Shrunk {
+    /// Renders the history as a test case which can be pasted into the game's test suite.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "// Reproduces: {}", self.error)?;
+        writeln!(f, "#[test]")?;
+        writeln!(f, "fn fuzz_regression() {{")?;
+        writeln!(f, "    let commands: Vec<(usize, String)> = vec![")?;
+        for &(player, ref command) in &self.commands {
+            writeln!(f, "        ({}, {:?}.to_string()),", player, command)?;
+        }
+        writeln!(f, "    ];")?;
+        writeln!(
+            f,
+            "    if let Err(e) = brdgme_game::bot::replay::<{}>({}, {}, &commands) {{",
+            self.game_type,
+            self.player_count,
+            self.seed
+        )?;
+        writeln!(f, "        panic!(\"{{:?}}\", e);")?;
+        writeln!(f, "    }}")?;
+        writeln!(f, "}}")
+    }
+}
+
+fn check<G: Gamer>(game: &G, len: usize) -> Option<Failure> {
+    invariants::check(game).err().map(|e| Failure {
+        kind: FailureKind::Invariant,
+        message: format!("invariant violated: {}", e),
+        len,
+    })
+}
+
+/// Replay the commands against a new game, returning the first error which isn't invalid input,
+/// the first invariant violation or the first panic.
+fn run<G: Gamer>(player_count: usize, seed: u64, commands: &[(usize, String)]) -> Option<Failure> {
+    let players = player_names(player_count);
+    let mut len = 0;
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let (mut game, _) = G::new_with_options(player_count, &G::Options::default(), seed).ok()?;
+        if let Some(failure) = check(&game, 0) {
+            return Some(failure);
+        }
+        for (i, &(player, ref command)) in commands.iter().enumerate() {
+            len = i + 1;
+            match game.command(player, command, &players) {
+                Ok(..) | Err(GameError::InvalidInput { .. }) => {}
+                Err(e) => {
+                    return Some(Failure {
+                        kind: FailureKind::Error(mem::discriminant(&e)),
+                        message: format!("{:?}", e),
+                        len,
+                    })
+                }
+            }
+            if let Some(failure) = check(&game, len) {
+                return Some(failure);
+            }
+        }
+        None
+    }));
+    match result {
+        Ok(failure) => failure,
+        Err(payload) => Some(Failure {
+            kind: FailureKind::Panic,
+            message: format!("panicked: {}", panic_message(&payload)),
+            len,
+        }),
+    }
+}
+
+/// Find a shorter command history which fails in the same way as `commands`, by repeatedly
+/// removing chunks of commands and keeping any removal which still fails with the same
+/// `GameError` variant, still violates an invariant or still panics. Returns `None` if
+/// `commands` doesn't fail at all.
+///
+/// Panics raised while shrinking are caught, but will still be printed by the panic hook.
+pub fn shrink<G: Gamer>(
+    player_count: usize,
+    seed: u64,
+    commands: &[(usize, String)],
+) -> Option<Shrunk> {
+    let mut failure = run::<G>(player_count, seed, commands)?;
+    let target = failure.kind;
+    let mut shrunk = commands[..failure.len].to_vec();
+    let mut chunk = cmp::max(shrunk.len() / 2, 1);
+    loop {
+        let mut removed = false;
+        let mut start = 0;
+        while start < shrunk.len() {
+            let end = cmp::min(start + chunk, shrunk.len());
+            let candidate = [&shrunk[..start], &shrunk[end..]].concat();
+            match run::<G>(player_count, seed, &candidate) {
+                Some(ref f) if f.kind == target => {
+                    shrunk = candidate[..f.len].to_vec();
+                    removed = true;
+                }
+                _ => start += chunk,
+            }
+        }
+        if chunk > 1 {
+            chunk /= 2;
+        } else if !removed {
+            break;
+        }
+    }
+    if let Some(f) = run::<G>(player_count, seed, &shrunk) {
+        failure = f;
+    }
+    Some(Shrunk {
+        game_type: any::type_name::<G>().to_string(),
+        player_count,
+        seed,
+        commands: shrunk,
+        error: failure.message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::replay;
+    use crate::test_game::{BrokenRace, Race, BROKEN_AT};
+
+    fn commands(commands: &[(usize, &str)]) -> Vec<(usize, String)> {
+        commands
+            .iter()
+            .map(|&(p, c)| (p, c.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn shrink_works() {
+        let history = commands(&[
+            (0, "add 1"),
+            (1, "blah"),
+            (1, "add 1"),
+            (0, "add 1"),
+            (1, "add 3"),
+            (0, "bacon"),
+            (0, "add 1"),
+            (1, "add 1"),
+            (0, "add 2"),
+        ]);
+        let shrunk = shrink::<BrokenRace>(2, 0, &history).expect("expected history to fail");
+        assert_eq!(
+            commands(&[
+                (0, "add 1"),
+                (1, "add 1"),
+                (0, "add 1"),
+                (1, "add 3"),
+                (0, "add 1"),
+            ]),
+            shrunk.commands
+        );
+        assert_eq!(
+            BROKEN_AT,
+            replay::<Race>(2, 0, &shrunk.commands)
+                .expect("expected Race to replay")
+                .total
+        );
+        assert!(
+            format!("{}", shrunk).contains("replay::<brdgme_game::test_game::BrokenRace>(2, 0,")
+        );
+    }
+
+    #[test]
+    fn shrink_ignores_passing_history() {
+        assert_eq!(None, shrink::<Race>(2, 0, &commands(&[(0, "add 1"), (1, "add 2")])));
+    }
+}
@@ -0,0 +1,267 @@
+use rand::{self, Rng};
+
+use crate::bot::{BotCommand, Botter};
+use crate::command::{CharClass, CoordForm, Spec as CommandSpec};
+use crate::game::Gamer;
+
+// Unbounded ints are generated within this distance of the bound that is known, or between zero
+// and this value if neither bound is known.
+const INT_RANGE: i64 = 100;
+// Unbounded `Many` specs generate up to this many items more than the minimum.
+const MANY_EXTRA: usize = 3;
+// Unbounded text is generated with up to this many characters more than the minimum.
+const TEXT_EXTRA: usize = 8;
+// Floats without a precision are generated with this many decimal places.
+const FLOAT_PRECISION: usize = 2;
+
+/// A bot which generates random, syntactically valid commands by walking the command spec. It
+/// knows nothing about the rules of the game, but is enough to give any `Gamer` fuzzing coverage.
+pub struct SpecBot<R: Rng> {
+    rng: R,
+}
+
+impl SpecBot<rand::ThreadRng> {
+    pub fn new() -> Self {
+        Self::from_rng(rand::thread_rng())
+    }
+}
+
+impl Default for SpecBot<rand::ThreadRng> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Rng> SpecBot<R> {
+    pub fn from_rng(rng: R) -> Self {
+        Self { rng }
+    }
+
+    pub fn generate(&mut self, spec: &CommandSpec, players: &[String]) -> String {
+        generate(spec, players, &mut self.rng)
+    }
+}
+
+impl<G: Gamer, R: Rng> Botter<G> for SpecBot<R> {
+    fn commands(
+        &mut self,
+        _player: usize,
+        _player_state: &G::PlayerState,
+        players: &[String],
+        command_spec: &CommandSpec,
+        _game_id: Option<String>,
+    ) -> Vec<BotCommand> {
+        vec![self.generate(command_spec, players).into()]
+    }
+}
+
+/// Generate a random input string which is accepted by the parser described by `spec`.
+pub fn generate<R: Rng>(spec: &CommandSpec, players: &[String], rng: &mut R) -> String {
+    let mut output = String::new();
+    generate_into(spec, players, rng, &mut output);
+    output
+}
+
+fn generate_into<R: Rng>(spec: &CommandSpec, players: &[String], rng: &mut R, output: &mut String) {
+    match *spec {
+        CommandSpec::Int { min, max } => output.push_str(&generate_int(min, max, rng).to_string()),
+        CommandSpec::Float {
+            min,
+            max,
+            precision,
+        } => output.push_str(&generate_float(min, max, precision, rng)),
+        CommandSpec::Text { min, max, chars } => {
+            output.push_str(&generate_text(min, max, chars, rng))
+        }
+        CommandSpec::Coord(ref form) => {
+            if let Some((x, y)) = generate_coord(form, rng) {
+                output.push_str(&form.format(x, y));
+            }
+        }
+        CommandSpec::Token(ref token) => output.push_str(token),
+        CommandSpec::Enum { ref values, .. } => {
+            if let Some(value) = rng.choose(values) {
+                output.push_str(value);
+            }
+        }
+        CommandSpec::OneOf(ref specs) => {
+            if let Some(spec) = rng.choose(specs) {
+                generate_into(spec, players, rng, output);
+            }
+        }
+        CommandSpec::Chain(ref specs) => {
+            for spec in specs {
+                generate_into(spec, players, rng, output);
+            }
+        }
+        CommandSpec::Many {
+            ref spec,
+            min,
+            max,
+            ref delim,
+        } => {
+            let min = min.unwrap_or(0);
+            let max = max.unwrap_or(min + MANY_EXTRA);
+            if max == 0 || max < min {
+                // The parser doesn't consume anything in these cases.
+                return;
+            }
+            for i in 0..rng.gen_range(min, max + 1) {
+                if i > 0 {
+                    output.push_str(delim);
+                    output.push(' ');
+                }
+                generate_into(spec, players, rng, output);
+            }
+        }
+        CommandSpec::Opt(ref spec) => {
+            if rng.gen() {
+                generate_into(spec, players, rng, output);
+            }
+        }
+        CommandSpec::Doc { ref spec, .. } => generate_into(spec, players, rng, output),
+        CommandSpec::Player => {
+            if let Some(name) = rng.choose(players) {
+                output.push_str(name);
+            }
+        }
+        CommandSpec::Space => output.push(' '),
+    }
+}
+
+fn generate_float<R: Rng>(
+    min: Option<f64>,
+    max: Option<f64>,
+    precision: Option<usize>,
+    rng: &mut R,
+) -> String {
+    let range = INT_RANGE as f64;
+    let (low, high) = match (min, max) {
+        (None, None) => (0.0, range),
+        (Some(min), None) => (min, min + range),
+        (None, Some(max)) => (max - range, max),
+        (Some(min), Some(max)) if min > max => (min, min),
+        (Some(min), Some(max)) => (min, max),
+    };
+    let precision = precision.unwrap_or(FLOAT_PRECISION).min(FLOAT_PRECISION);
+    let scale = 10f64.powi(precision as i32);
+    // Round inwards so the value stays within the bounds.
+    let steps = ((low * scale).ceil(), (high * scale).floor());
+    let value = if steps.0 >= steps.1 {
+        steps.0
+    } else {
+        rng.gen_range(steps.0, steps.1 + 1.0).floor()
+    };
+    format!("{:.p$}", value / scale, p = precision)
+}
+
+/// Text is always generated as a bare word so it never needs quoting.
+fn generate_text<R: Rng>(
+    min: Option<usize>,
+    max: Option<usize>,
+    chars: CharClass,
+    rng: &mut R,
+) -> String {
+    let alphabet: &[u8] = match chars {
+        CharClass::Alphanumeric => b"abcdefghijklmnopqrstuvwxyz0123456789",
+        _ => b"abcdefghijklmnopqrstuvwxyz",
+    };
+    // Bare words can't be empty.
+    let min = min.unwrap_or(0).max(1);
+    let max = max.unwrap_or(min + TEXT_EXTRA).max(min);
+    (0..rng.gen_range(min, max + 1))
+        .map(|_| *rng.choose(alphabet).unwrap() as char)
+        .collect()
+}
+
+fn generate_coord<R: Rng>(form: &CoordForm, rng: &mut R) -> Option<(i32, i32)> {
+    match *form {
+        CoordForm::Grid {
+            ref columns,
+            ref rows,
+            ..
+        } => {
+            if columns.size == 0 || rows.size == 0 {
+                return None;
+            }
+            Some((
+                rng.gen_range(0, columns.size) as i32,
+                rng.gen_range(0, rows.size) as i32,
+            ))
+        }
+        CoordForm::Axial { radius } => {
+            let radius = radius as i32;
+            let r = rng.gen_range(-radius, radius + 1);
+            // q is limited so q + r stays within the radius.
+            let q = rng.gen_range((-radius).max(-radius - r), radius.min(radius - r) + 1);
+            Some((q, r))
+        }
+    }
+}
+
+fn generate_int<R: Rng>(min: Option<i32>, max: Option<i32>, rng: &mut R) -> i32 {
+    let (low, high) = match (min.map(i64::from), max.map(i64::from)) {
+        (None, None) => (0, INT_RANGE),
+        (Some(min), None) => (min, min + INT_RANGE),
+        (None, Some(max)) => (max - INT_RANGE, max),
+        (Some(min), Some(max)) if min > max => (min, min),
+        (Some(min), Some(max)) => (min, max),
+    };
+    let low = low.max(i64::from(i32::MIN));
+    let high = high.min(i64::from(i32::MAX));
+    rng.gen_range(low, high + 1) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::parser::*;
+
+    #[test]
+    fn generate_matches_parser() {
+        let players = vec!["mick".to_string(), "steve".to_string()];
+        let parsers: Vec<Box<dyn Parser<String>>> = vec![
+            Box::new(Map::new(
+                Chain2::new(Token::new("bid"), AfterSpace::new(Int::bounded(-5, 5))),
+                |(_, i)| i.to_string(),
+            )),
+            Box::new(Map::new(
+                Chain2::new(Token::new("give"), AfterSpace::new(Player {})),
+                |(_, p)| p.to_string(),
+            )),
+            Box::new(Map::new(
+                Chain3::new(
+                    Token::new("play"),
+                    AfterSpace::new(Many::bounded(
+                        Enum::partial(vec!["cheese", "bacon", "egg"]),
+                        1,
+                        3,
+                    )),
+                    Opt::new(AfterSpace::new(Int::positive())),
+                ),
+                |(_, cards, n)| format!("{:?} {:?}", cards, n),
+            )),
+        ];
+        let parser = OneOf::new(parsers);
+        let spec = parser.to_spec();
+        let mut bot = SpecBot::new();
+        for _ in 0..1000 {
+            let input = bot.generate(&spec, &players);
+            let output = parser
+                .parse(&input, &players)
+                .unwrap_or_else(|e| panic!("expected '{}' to parse: {}", input, e));
+            assert_eq!("", output.remaining, "for input '{}'", input);
+        }
+    }
+
+    #[test]
+    fn generate_int_respects_bounds() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let i = generate_int(Some(3), Some(7), &mut rng);
+            assert!((3..=7).contains(&i));
+            assert!(generate_int(Some(i32::MAX - 1), None, &mut rng) >= i32::MAX - 1);
+            assert!(generate_int(None, Some(-20), &mut rng) <= -20);
+        }
+    }
+}
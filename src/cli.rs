@@ -0,0 +1,290 @@
+use rand::{self, Rng};
+
+use brdgme_color;
+use brdgme_markup::{self as markup, Node};
+
+use std::io::{self, BufRead, Write};
+
+use crate::command::doc;
+use crate::game::{Gamer, Renderer, Status};
+use crate::game_log::Log;
+
+/// Play a game in the terminal, with all players taking turns at the same keyboard.
+pub fn run<G: Gamer>() -> io::Result<()> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    run_with::<G, _, _>(stdin.lock(), &mut stdout.lock())
+}
+
+/// Converts transformed markup to text for the terminal.
+type Render = fn(&[markup::TNode]) -> String;
+
+/// Play a game reading commands from `input` and writing the game to `output`.
+pub fn run_with<G, I, O>(input: I, output: &mut O) -> io::Result<()>
+where
+    G: Gamer,
+    I: BufRead,
+    O: Write,
+{
+    play::<G, _, _>(input, output, markup::ansi)
+}
+
+fn play<G, I, O>(mut input: I, output: &mut O, render: Render) -> io::Result<()>
+where
+    G: Gamer,
+    I: BufRead,
+    O: Write,
+{
+    let names = match read_names::<G, _, _>(&mut input, output)? {
+        Some(names) => names,
+        None => return Ok(()),
+    };
+    let players = markup_players(&names);
+    let options = match read_options::<G, _, _>(&mut input, output, render)? {
+        Some(options) => options,
+        None => return Ok(()),
+    };
+    let (mut game, logs) =
+        match G::new_with_options(names.len(), &options, rand::thread_rng().gen()) {
+            Ok(created) => created,
+            Err(e) => {
+                writeln!(output, "Failed to create game: {}", e)?;
+                return Ok(());
+            }
+        };
+    // Private logs are held until it's the turn of the player they're for.
+    let mut pending: Vec<Vec<Log>> = vec![vec![]; names.len()];
+    write_logs(output, logs, &mut pending, &players, render)?;
+    // Players whose turn it is at the same time are prompted one after the other, a player is
+    // prompted again if their command fails.
+    let mut last_player: Option<usize> = None;
+    loop {
+        let player = match game.status() {
+            Status::Finished { placings, .. } => {
+                write_nodes(output, &game.pub_state().render(), &players, render)?;
+                writeln!(output, "\nGame over")?;
+                for (p, place) in placings.iter().enumerate() {
+                    write_nodes(
+                        output,
+                        &[Node::Player(p), Node::text(format!(": {}", place))],
+                        &players,
+                        render,
+                    )?;
+                    writeln!(output)?;
+                }
+                return Ok(());
+            }
+            Status::Active { whose_turn, .. } => match next_player(&whose_turn, last_player) {
+                Some(p) => p,
+                None => {
+                    writeln!(output, "It is nobody's turn")?;
+                    return Ok(());
+                }
+            },
+        };
+        writeln!(output)?;
+        write_nodes(
+            output,
+            &[Node::text("--- "), Node::Player(player), Node::text("'s turn ---\n")],
+            &players,
+            render,
+        )?;
+        for log in pending[player].drain(..) {
+            write_nodes(output, &log.content, &players, render)?;
+            writeln!(output)?;
+        }
+        write_nodes(output, &game.player_state(player).render(), &players, render)?;
+        writeln!(output)?;
+        if let Some(spec) = game.command_spec(player) {
+            write_nodes(output, &doc::render(&spec.doc()), &players, render)?;
+            writeln!(output)?;
+        }
+        let mut line = match prompt(&mut input, output, "> ")? {
+            Some(line) => line,
+            None => return Ok(()),
+        };
+        // Input left over by a command is run as another command until none is left.
+        loop {
+            match game.command(player, &line, &names) {
+                Ok(response) => {
+                    last_player = Some(player);
+                    write_logs(output, response.logs, &mut pending, &players, render)?;
+                    line = response.remaining_input.trim().to_string();
+                    if line.is_empty() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    write_nodes(output, &e.diagnostic(&line), &players, render)?;
+                    writeln!(output)?;
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// The first player after `last` whose turn it is, wrapping around to the start.
+fn next_player(whose_turn: &[usize], last: Option<usize>) -> Option<usize> {
+    let mut players = whose_turn.to_vec();
+    players.sort();
+    match last {
+        Some(last) => players
+            .iter()
+            .find(|&&p| p > last)
+            .or_else(|| players.first())
+            .cloned(),
+        None => players.first().cloned(),
+    }
+}
+
+fn markup_players(names: &[String]) -> Vec<markup::Player> {
+    names
+        .iter()
+        .enumerate()
+        .map(|(p, name)| markup::Player {
+            name: name.to_owned(),
+            color: brdgme_color::player_color(p).to_owned(),
+        })
+        .collect()
+}
+
+fn write_nodes<O: Write>(
+    output: &mut O,
+    nodes: &[Node],
+    players: &[markup::Player],
+    render: Render,
+) -> io::Result<()> {
+    write!(output, "{}", render(&markup::transform(nodes, players)))
+}
+
+fn write_logs<O: Write>(
+    output: &mut O,
+    logs: Vec<Log>,
+    pending: &mut [Vec<Log>],
+    players: &[markup::Player],
+    render: Render,
+) -> io::Result<()> {
+    for log in logs {
+        if log.public {
+            write_nodes(output, &log.content, players, render)?;
+            writeln!(output)?;
+        } else {
+            for &p in &log.to {
+                if let Some(p_pending) = pending.get_mut(p) {
+                    p_pending.push(log.clone());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write the prompt and read a trimmed line, returning `None` at the end of the input.
+fn prompt<I: BufRead, O: Write>(
+    input: &mut I,
+    output: &mut O,
+    text: &str,
+) -> io::Result<Option<String>> {
+    write!(output, "{}", text)?;
+    output.flush()?;
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+}
+
+fn read_names<G, I, O>(input: &mut I, output: &mut O) -> io::Result<Option<Vec<String>>>
+where
+    G: Gamer,
+    I: BufRead,
+    O: Write,
+{
+    let player_counts = G::player_counts();
+    loop {
+        let line = match prompt(
+            input,
+            output,
+            &format!(
+                "Enter player names separated by commas ({} players): ",
+                player_counts
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        )? {
+            Some(line) => line,
+            None => return Ok(None),
+        };
+        let names = line.split(',')
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+            .collect::<Vec<String>>();
+        if player_counts.contains(&names.len()) {
+            return Ok(Some(names));
+        }
+        writeln!(output, "Can't play with {} players", names.len())?;
+    }
+}
+
+fn read_options<G, I, O>(
+    input: &mut I,
+    output: &mut O,
+    render: Render,
+) -> io::Result<Option<G::Options>>
+where
+    G: Gamer,
+    I: BufRead,
+    O: Write,
+{
+    let spec = match G::options_spec() {
+        Some(spec) => spec,
+        None => return Ok(Some(G::Options::default())),
+    };
+    writeln!(output, "Options, leave blank for the defaults:")?;
+    write_nodes(output, &doc::render(&spec.doc()), &[], render)?;
+    writeln!(output)?;
+    loop {
+        let line = match prompt(input, output, "> ")? {
+            Some(line) => line,
+            None => return Ok(None),
+        };
+        match G::parse_options(&line) {
+            Ok(options) => return Ok(Some(options)),
+            Err(e) => writeln!(output, "{}", e)?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_game::Race;
+
+    #[test]
+    fn run_with_works() {
+        let input = "mick\nmick, steve\ntarget 5\nadd 1 add 2\nadd 4\nadd 2\nadd 2\n";
+        let mut output: Vec<u8> = vec![];
+        play::<Race, _, _>(input.as_bytes(), &mut output, markup::plain)
+            .expect("expected run to succeed");
+        let output = String::from_utf8(output).expect("expected output to be UTF-8");
+        assert!(output.contains("Can't play with 1 players"));
+        assert!(output.contains("--- steve's turn ---"));
+        assert!(output.contains("number between 1 and 3"));
+        // The second command on a line is run, rather than dropped, and fails as it isn't mick's
+        // turn any more.
+        assert!(output.contains("not your turn"), "unexpected output: {}", output);
+        assert!(output.contains("Game over"));
+    }
+
+    #[test]
+    fn next_player_works() {
+        assert_eq!(None, next_player(&[], None));
+        assert_eq!(Some(1), next_player(&[2, 1], None));
+        assert_eq!(Some(2), next_player(&[2, 1], Some(1)));
+        assert_eq!(Some(1), next_player(&[2, 1], Some(2)));
+        assert_eq!(Some(0), next_player(&[0], Some(0)));
+    }
+}
@@ -0,0 +1,321 @@
+use serde_derive::{Deserialize, Serialize};
+
+use std::collections::BTreeSet;
+
+use crate::command::parser::{Enum, Float, Int, Parser, Player, Text, Token};
+use crate::command::{CoordForm, Spec};
+
+/// A valid continuation of partial input. `offset` is the byte offset in the input where the
+/// completion starts, any input after it is a partial match and would be replaced.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Completion {
+    Text { offset: usize, text: String },
+    Int {
+        offset: usize,
+        min: Option<i32>,
+        max: Option<i32>,
+    },
+    Coord { offset: usize, form: CoordForm },
+}
+
+impl Spec {
+    /// Find the valid continuations at the end of `input`, which should be the input up to the
+    /// cursor. Use `to_spec` to complete for a `Parser`.
+    pub fn complete(&self, input: &str, names: &[String]) -> Vec<Completion> {
+        let mut completer = Completer {
+            input,
+            names,
+            completions: BTreeSet::new(),
+        };
+        completer.ends(self, 0);
+        completer.completions.into_iter().collect()
+    }
+}
+
+struct Completer<'a> {
+    input: &'a str,
+    names: &'a [String],
+    completions: BTreeSet<Completion>,
+}
+
+fn is_partial(input: &str, candidate: &str) -> bool {
+    input.len() < candidate.len() && candidate.to_lowercase().starts_with(&input.to_lowercase())
+}
+
+fn is_partial_coord(input: &str, form: &CoordForm) -> bool {
+    let separator = match *form {
+        CoordForm::Grid { ref separator, .. } => separator.to_owned().unwrap_or_default(),
+        CoordForm::Axial { .. } => ",".to_string(),
+    };
+    input
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || separator.contains(c))
+}
+
+fn is_partial_int(input: &str) -> bool {
+    input
+        .chars()
+        .enumerate()
+        .all(|(i, c)| (i == 0 && c == '-') || c.is_ascii_digit())
+}
+
+impl<'a> Completer<'a> {
+    /// Find every position at which `spec` can finish matching when starting at `pos`, recording
+    /// completions wherever the input runs out part way through.
+    fn ends(&mut self, spec: &Spec, pos: usize) -> BTreeSet<usize> {
+        let remaining = &self.input[pos..];
+        match *spec {
+            Spec::Int { min, max } => {
+                if is_partial_int(remaining) {
+                    self.completions.insert(Completion::Int {
+                        offset: pos,
+                        min,
+                        max,
+                    });
+                }
+                let consumed = Int { min, max }
+                    .parse(remaining, self.names)
+                    .ok()
+                    .map(|o| o.consumed.len());
+                self.leaf(pos, consumed)
+            }
+            Spec::Float {
+                min,
+                max,
+                precision,
+            } => {
+                let consumed = Float {
+                    min,
+                    max,
+                    precision,
+                }.parse(remaining, self.names)
+                    .ok()
+                    .map(|o| o.consumed.len());
+                self.leaf(pos, consumed)
+            }
+            Spec::Text { min, max, chars } => {
+                let consumed = Text { min, max, chars }
+                    .parse(remaining, self.names)
+                    .ok()
+                    .map(|o| o.consumed.len());
+                self.leaf(pos, consumed)
+            }
+            Spec::Coord(ref form) => {
+                if is_partial_coord(remaining, form) {
+                    self.completions.insert(Completion::Coord {
+                        offset: pos,
+                        form: form.to_owned(),
+                    });
+                }
+                let consumed = form
+                    .parse(remaining, self.names)
+                    .ok()
+                    .map(|o| o.consumed.len());
+                self.leaf(pos, consumed)
+            }
+            Spec::Token(ref token) => {
+                self.text(pos, token);
+                let consumed = Token::new(token.to_owned())
+                    .parse(remaining, self.names)
+                    .ok()
+                    .map(|o| o.consumed.len());
+                self.leaf(pos, consumed)
+            }
+            Spec::Enum {
+                ref values,
+                exact,
+                ref aliases,
+            } => {
+                for value in values.iter().chain(aliases.iter().flat_map(|a| a.iter())) {
+                    self.text(pos, value);
+                }
+                let consumed = Enum {
+                    values: values.clone(),
+                    exact,
+                    aliases: aliases.clone(),
+                }.parse(remaining, self.names)
+                    .ok()
+                    .map(|o| o.consumed.len());
+                self.leaf(pos, consumed)
+            }
+            Spec::OneOf(ref specs) => specs.iter().flat_map(|s| self.ends(s, pos)).collect(),
+            Spec::Chain(ref specs) => {
+                let mut positions: BTreeSet<usize> = BTreeSet::new();
+                positions.insert(pos);
+                for s in specs {
+                    positions = positions.iter().flat_map(|&p| self.ends(s, p)).collect();
+                }
+                positions
+            }
+            Spec::Many {
+                ref spec,
+                min,
+                max,
+                ref delim,
+            } => self.many(spec, min.unwrap_or(0), max, delim, pos),
+            Spec::Opt(ref spec) => {
+                let mut positions = self.ends(spec, pos);
+                positions.insert(pos);
+                positions
+            }
+            Spec::Doc { ref spec, .. } => self.ends(spec, pos),
+            Spec::Player => {
+                for name in self.names {
+                    self.text(pos, name);
+                }
+                let consumed = Player {}
+                    .parse(remaining, self.names)
+                    .ok()
+                    .map(|o| o.consumed.len());
+                self.leaf(pos, consumed)
+            }
+            Spec::Space => {
+                if remaining.is_empty() {
+                    self.text(pos, " ");
+                }
+                match self.skip_space(pos) {
+                    end if end > pos => self.leaf(pos, Some(end - pos)),
+                    _ => BTreeSet::new(),
+                }
+            }
+        }
+    }
+
+    fn many(
+        &mut self,
+        spec: &Spec,
+        min: usize,
+        max: Option<usize>,
+        delim: &str,
+        pos: usize,
+    ) -> BTreeSet<usize> {
+        let mut positions: BTreeSet<usize> = BTreeSet::new();
+        if let Some(max) = max {
+            if max == 0 || max < min {
+                positions.insert(pos);
+                return positions;
+            }
+        }
+        if min == 0 {
+            positions.insert(pos);
+        }
+        let mut frontier = self.ends(spec, pos);
+        let mut count = 1;
+        // Each item after the first needs a delimiter, so the count can't exceed the input length
+        // unless the delimiter is empty.
+        while !frontier.is_empty() && count <= self.input.len() + 1 {
+            if count >= min {
+                positions.extend(frontier.iter().cloned());
+            }
+            if max.map(|max| count >= max).unwrap_or(false) {
+                break;
+            }
+            let mut next: BTreeSet<usize> = BTreeSet::new();
+            for p in frontier {
+                let delim_start = self.skip_space(p);
+                self.text(delim_start, delim);
+                if let Ok(o) = Token::new(delim).parse(&self.input[delim_start..], self.names) {
+                    let item_start = self.skip_space(delim_start + o.consumed.len());
+                    next.extend(self.ends(spec, item_start));
+                }
+            }
+            frontier = next;
+            count += 1;
+        }
+        positions
+    }
+
+    fn skip_space(&self, pos: usize) -> usize {
+        pos + self.input[pos..]
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .map(|c| c.len_utf8())
+            .sum::<usize>()
+    }
+
+    /// Record a text completion if the rest of the input is a partial match for it.
+    fn text(&mut self, pos: usize, text: &str) {
+        if is_partial(&self.input[pos..], text) {
+            self.completions.insert(Completion::Text {
+                offset: pos,
+                text: text.to_owned(),
+            });
+        }
+    }
+
+    fn leaf(&self, pos: usize, consumed: Option<usize>) -> BTreeSet<usize> {
+        consumed.into_iter().map(|c| pos + c).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::parser::*;
+
+    fn spec() -> Spec {
+        let parsers: Vec<Box<dyn Parser<String>>> = vec![
+            Box::new(Map::new(
+                Chain2::new(Token::new("bid"), AfterSpace::new(Int::bounded(-5, 5))),
+                |(_, i)| i.to_string(),
+            )),
+            Box::new(Map::new(
+                Chain2::new(Token::new("give"), AfterSpace::new(Player {})),
+                |(_, p)| p.to_string(),
+            )),
+            Box::new(Map::new(
+                Chain3::new(
+                    Token::new("play"),
+                    AfterSpace::new(Many::some(Enum::partial(vec!["cheese", "bacon", "egg"]))),
+                    Opt::new(AfterSpace::new(Int::positive())),
+                ),
+                |(_, cards, n)| format!("{:?} {:?}", cards, n),
+            )),
+        ];
+        OneOf::new(parsers).to_spec()
+    }
+
+    fn text(offset: usize, text: &str) -> Completion {
+        Completion::Text {
+            offset,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn complete_works() {
+        let names = vec!["mick".to_string(), "steve".to_string()];
+        let spec = spec();
+        assert_eq!(
+            vec![text(0, "bid"), text(0, "give"), text(0, "play")],
+            spec.complete("", &names)
+        );
+        assert_eq!(vec![text(0, "play")], spec.complete("P", &names));
+        assert_eq!(vec![text(4, " ")], spec.complete("play", &names));
+        assert_eq!(
+            vec![text(5, "bacon"), text(5, "cheese"), text(5, "egg")],
+            spec.complete("play ", &names)
+        );
+        assert_eq!(
+            vec![text(5, "cheese"), text(7, " "), text(7, ",")],
+            spec.complete("play ch", &names)
+        );
+        assert_eq!(
+            vec![text(14, "bacon"), text(14, "cheese"), text(14, "egg")],
+            spec.complete("play cheese , ", &names)
+        );
+        assert_eq!(
+            vec![text(5, "mick"), text(5, "steve")],
+            spec.complete("give ", &names)
+        );
+        assert_eq!(
+            vec![Completion::Int {
+                offset: 4,
+                min: Some(-5),
+                max: Some(5),
+            }],
+            spec.complete("bid -", &names)
+        );
+        assert_eq!(Vec::<Completion>::new(), spec.complete("blah", &names));
+    }
+}
@@ -0,0 +1,213 @@
+use serde_derive::{Deserialize, Serialize};
+
+/// How the positions along an axis are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Labels {
+    /// "a" to "z", then "aa", "ab" and so on like spreadsheet columns. Case insensitive.
+    Letters,
+    /// Numbers counting up from `start`.
+    Numbers { start: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Axis {
+    pub size: usize,
+    pub labels: Labels,
+}
+
+/// The shape of a board and how positions on it are written.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum CoordForm {
+    /// A column then a row, with an optional separator between them, so "c12" or "3,4".
+    Grid {
+        columns: Axis,
+        rows: Axis,
+        separator: Option<String>,
+    },
+    /// Axial hex coordinates written as "q,r", on a hexagonal board with `radius` rings around
+    /// the centre hex "0,0".
+    Axial { radius: usize },
+}
+
+impl Labels {
+    pub fn label(self, index: usize) -> String {
+        match self {
+            Labels::Letters => {
+                let mut n = index + 1;
+                let mut label = vec![];
+                while n > 0 {
+                    n -= 1;
+                    label.push((b'a' + (n % 26) as u8) as char);
+                    n /= 26;
+                }
+                label.into_iter().rev().collect()
+            }
+            Labels::Numbers { start } => (start + index).to_string(),
+        }
+    }
+
+    /// The byte length of the label at the start of the input.
+    pub fn label_len(self, input: &str) -> usize {
+        input
+            .chars()
+            .take_while(|c| match self {
+                Labels::Letters => c.is_ascii_alphabetic(),
+                Labels::Numbers { .. } => c.is_ascii_digit(),
+            })
+            .count()
+    }
+
+    /// The index of a label, `None` if it isn't a label or comes before the first one.
+    pub fn index(self, label: &str) -> Option<usize> {
+        if label.is_empty() || label.len() > 9 {
+            return None;
+        }
+        match self {
+            Labels::Letters => label
+                .to_lowercase()
+                .bytes()
+                .try_fold(0, |n: usize, b| match b {
+                    b'a'..=b'z' => Some(n * 26 + (b - b'a') as usize + 1),
+                    _ => None,
+                })
+                .map(|n| n - 1),
+            Labels::Numbers { start } => label
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(start)),
+        }
+    }
+}
+
+impl Axis {
+    pub fn new(size: usize, labels: Labels) -> Self {
+        Axis { size, labels }
+    }
+
+    pub fn letters(size: usize) -> Self {
+        Axis::new(size, Labels::Letters)
+    }
+
+    /// Numbers starting from 1.
+    pub fn numbers(size: usize) -> Self {
+        Axis::new(size, Labels::Numbers { start: 1 })
+    }
+}
+
+impl CoordForm {
+    /// Letter columns directly followed by number rows, like "a1" to "h8" on a chess board.
+    pub fn chess(columns: usize, rows: usize) -> Self {
+        CoordForm::Grid {
+            columns: Axis::letters(columns),
+            rows: Axis::numbers(rows),
+            separator: None,
+        }
+    }
+
+    /// Column and row numbers separated by a comma, like "3,4".
+    pub fn pair(columns: usize, rows: usize) -> Self {
+        CoordForm::Grid {
+            columns: Axis::numbers(columns),
+            rows: Axis::numbers(rows),
+            separator: Some(",".to_string()),
+        }
+    }
+
+    pub fn axial(radius: usize) -> Self {
+        CoordForm::Axial { radius }
+    }
+
+    /// Write a position, being a column and row for grids or q and r for axial coordinates.
+    pub fn format(&self, x: i32, y: i32) -> String {
+        match *self {
+            CoordForm::Grid {
+                ref columns,
+                ref rows,
+                ref separator,
+            } => format!(
+                "{}{}{}",
+                columns.labels.label(x as usize),
+                separator.as_ref().map(|s| s.as_ref()).unwrap_or(""),
+                rows.labels.label(y as usize)
+            ),
+            CoordForm::Axial { .. } => format!("{},{}", x, y),
+        }
+    }
+
+    /// Every position on the board, rows first for grids.
+    pub fn positions(&self) -> Vec<(i32, i32)> {
+        match *self {
+            CoordForm::Grid {
+                ref columns,
+                ref rows,
+                ..
+            } => (0..rows.size as i32)
+                .flat_map(|y| (0..columns.size as i32).map(move |x| (x, y)))
+                .collect(),
+            CoordForm::Axial { radius } => {
+                let radius = radius as i32;
+                (-radius..=radius)
+                    .flat_map(|r| {
+                        (-radius..=radius)
+                            .filter(move |q| (q + r).abs() <= radius)
+                            .map(move |q| (q, r))
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// The number of positions on the board.
+    pub fn size(&self) -> usize {
+        match *self {
+            CoordForm::Grid {
+                ref columns,
+                ref rows,
+                ..
+            } => columns.size.saturating_mul(rows.size),
+            CoordForm::Axial { radius } => 3 * radius * (radius + 1) + 1,
+        }
+    }
+
+    /// A short description of the board, such as "a1-h8" or "q,r within 3 of 0,0".
+    pub fn describe(&self) -> String {
+        match *self {
+            CoordForm::Grid {
+                ref columns,
+                ref rows,
+                ..
+            } => {
+                if columns.size == 0 || rows.size == 0 {
+                    return "no positions".to_string();
+                }
+                format!(
+                    "{}-{}",
+                    self.format(0, 0),
+                    self.format(columns.size as i32 - 1, rows.size as i32 - 1)
+                )
+            }
+            CoordForm::Axial { radius } => format!("q,r within {} of 0,0", radius),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_work() {
+        for &(index, label) in &[(0, "a"), (25, "z"), (26, "aa"), (701, "zz"), (702, "aaa")] {
+            assert_eq!(label, Labels::Letters.label(index));
+            assert_eq!(Some(index), Labels::Letters.index(label));
+        }
+        assert_eq!(Some(27), Labels::Letters.index("AB"));
+        assert_eq!("1", Labels::Numbers { start: 1 }.label(0));
+        assert_eq!(Some(0), Labels::Numbers { start: 0 }.index("0"));
+        assert_eq!(None, Labels::Numbers { start: 1 }.index("0"));
+        assert_eq!(19, CoordForm::axial(2).positions().len());
+        assert_eq!(19, CoordForm::axial(2).size());
+        assert_eq!("a1-h8", CoordForm::chess(8, 8).describe());
+        assert_eq!("1,1-3,4", CoordForm::pair(3, 4).describe());
+    }
+}
@@ -22,8 +22,15 @@ impl Spec {
     fn doc_opts(&self, opts: &Opts) -> Vec<(Vec<Node>, Option<String>)> {
         match *self {
             Spec::Int { min, max } => vec![(doc_int(min, max), None)],
+            Spec::Float { min, max, .. } => vec![(doc_float(min, max), None)],
+            Spec::Text { chars, .. } => vec![(vec![Node::text(chars.noun())], None)],
+            Spec::Coord(ref form) => vec![(vec![Node::text(form.describe())], None)],
             Spec::Token(ref token) => vec![(doc_token(token), None)],
-            Spec::Enum { ref values, .. } => vec![(doc_enum(values, opts), None)],
+            Spec::Enum {
+                ref values,
+                ref aliases,
+                ..
+            } => vec![(doc_enum(values, aliases, opts), None)],
             Spec::OneOf(ref specs) => doc_one_of(specs, opts),
             Spec::Chain(ref specs) => vec![doc_chain(specs, opts)],
             Spec::Many {
@@ -67,14 +74,35 @@ fn doc_int(min: Option<i32>, max: Option<i32>) -> Vec<Node> {
     }
 }
 
+fn doc_float(min: Option<f64>, max: Option<f64>) -> Vec<Node> {
+    match (min, max) {
+        (None, None) => vec![Node::text("#.#")],
+        (Some(min), Some(max)) if min == max => {
+            vec![Node::Bold(vec![Node::text(format!("{}", min))])]
+        }
+        (Some(min), Some(max)) => vec![Node::text(format!("{}-{}", min, max))],
+        (None, Some(max)) => vec![Node::text(format!("{} or lower", max))],
+        (Some(min), None) => vec![Node::text(format!("{}+", min))],
+    }
+}
+
 fn doc_token(token: &str) -> Vec<Node> {
     vec![Node::Bold(vec![Node::text(token)])]
 }
 
-fn doc_enum(values: &[String], opts: &Opts) -> Vec<Node> {
+fn doc_enum(values: &[String], aliases: &[Vec<String>], opts: &Opts) -> Vec<Node> {
     if let Some(ref name) = opts.name {
         return vec![Node::text(format!("[{}]", name))];
     }
+    // Aliases follow their value separated by slashes, eg. "hearts/h".
+    let values = values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| match aliases.get(i) {
+            Some(a) if !a.is_empty() => format!("{}/{}", v, a.join("/")),
+            _ => v.to_owned(),
+        })
+        .collect::<Vec<String>>();
     vec![Node::text(format!("[{}]", values.join(" | ")))]
 }
 
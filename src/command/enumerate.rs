@@ -0,0 +1,162 @@
+use crate::command::Spec;
+
+impl Spec {
+    /// List every input accepted by the spec, or `None` if there are infinitely many or more
+    /// than `limit`. Inputs are written the same way `SpecBot` writes them.
+    pub fn enumerate(&self, players: &[String], limit: usize) -> Option<Vec<String>> {
+        let inputs = match *self {
+            Spec::Int {
+                min: Some(min),
+                max: Some(max),
+            } => {
+                if i64::from(max) - i64::from(min) >= limit as i64 {
+                    return None;
+                }
+                (min..=max).map(|i| i.to_string()).collect()
+            }
+            Spec::Int { .. } => return None,
+            Spec::Float {
+                min: Some(min),
+                max: Some(max),
+                precision: Some(precision),
+            } => {
+                let scale = 10f64.powi(precision as i32);
+                let (low, high) = ((min * scale).ceil(), (max * scale).floor());
+                if high - low >= limit as f64 {
+                    return None;
+                }
+                let mut inputs = vec![];
+                let mut step = low;
+                while step <= high {
+                    inputs.push(format!("{:.p$}", step / scale, p = precision));
+                    step += 1.0;
+                }
+                inputs
+            }
+            Spec::Float { .. } | Spec::Text { .. } => return None,
+            Spec::Coord(ref form) => {
+                if form.size() > limit {
+                    return None;
+                }
+                form.positions()
+                    .into_iter()
+                    .map(|(x, y)| form.format(x, y))
+                    .collect()
+            }
+            Spec::Token(ref token) => vec![token.to_owned()],
+            Spec::Enum { ref values, .. } => values.clone(),
+            Spec::OneOf(ref specs) => {
+                let mut inputs: Vec<String> = vec![];
+                for spec in specs {
+                    inputs.extend(spec.enumerate(players, limit)?);
+                    if inputs.len() > limit {
+                        return None;
+                    }
+                }
+                inputs
+            }
+            Spec::Chain(ref specs) => {
+                let mut inputs = vec![String::new()];
+                for spec in specs {
+                    inputs = product(&inputs, &spec.enumerate(players, limit)?, "", limit)?;
+                }
+                inputs
+            }
+            Spec::Many {
+                ref spec,
+                min,
+                max: Some(max),
+                ref delim,
+            } => {
+                let min = min.unwrap_or(0);
+                if max == 0 || max < min {
+                    // The parser doesn't consume anything in these cases.
+                    return Some(vec![String::new()]);
+                }
+                let items = spec.enumerate(players, limit)?;
+                let delim = format!("{} ", delim);
+                let mut inputs: Vec<String> = vec![];
+                if min == 0 {
+                    inputs.push(String::new());
+                }
+                let mut current = items.clone();
+                for count in 1..=max {
+                    if count >= min {
+                        inputs.extend(current.iter().cloned());
+                    }
+                    if count < max {
+                        current = product(&current, &items, &delim, limit)?;
+                    }
+                }
+                inputs
+            }
+            Spec::Many { .. } => return None,
+            Spec::Opt(ref spec) => {
+                let mut inputs = vec![String::new()];
+                inputs.extend(spec.enumerate(players, limit)?);
+                inputs
+            }
+            Spec::Doc { ref spec, .. } => spec.enumerate(players, limit)?,
+            Spec::Player => players.to_vec(),
+            Spec::Space => vec![" ".to_string()],
+        };
+        if inputs.len() > limit {
+            None
+        } else {
+            Some(inputs)
+        }
+    }
+}
+
+/// Every combination of a prefix from `a` and a suffix from `b`.
+fn product(a: &[String], b: &[String], delim: &str, limit: usize) -> Option<Vec<String>> {
+    if a.len().saturating_mul(b.len()) > limit {
+        return None;
+    }
+    Some(
+        a.iter()
+            .flat_map(|a| b.iter().map(move |b| format!("{}{}{}", a, delim, b)))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::parser::*;
+
+    #[test]
+    fn enumerate_works() {
+        let names = vec!["mick".to_string(), "steve".to_string()];
+        let parser = OneOf::new(vec![
+            Box::new(Map::new(
+                Chain2::new(Token::new("bid"), AfterSpace::new(Int::bounded(1, 3))),
+                |(_, i)| i.to_string(),
+            )) as Box<dyn Parser<String>>,
+            Box::new(Map::new(
+                Chain3::new(
+                    Token::new("give"),
+                    AfterSpace::new(Player {}),
+                    Opt::new(AfterSpace::new(Many::bounded(Enum::exact(vec!["a", "b"]), 1, 2))),
+                ),
+                |(_, p, _)| p.to_string(),
+            )),
+        ]);
+        let inputs = parser
+            .to_spec()
+            .enumerate(&names, 100)
+            .expect("expected spec to be finite");
+        assert_eq!(3 + 2 * 7, inputs.len());
+        assert!(inputs.contains(&"bid 2".to_string()));
+        assert!(inputs.contains(&"give steve".to_string()));
+        assert!(inputs.contains(&"give mick b, a".to_string()));
+        for input in &inputs {
+            let output = parser
+                .parse(input, &names)
+                .unwrap_or_else(|e| panic!("expected '{}' to parse: {}", input, e));
+            assert_eq!("", output.remaining, "for input '{}'", input);
+        }
+        assert_eq!(None, parser.to_spec().enumerate(&names, 10));
+        assert_eq!(None, Int::positive().to_spec().enumerate(&names, 100));
+        assert_eq!(None, Many::any(Token::new("a")).to_spec().enumerate(&names, 100));
+    }
+}
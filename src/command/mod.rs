@@ -1,13 +1,35 @@
 pub mod parser;
 pub mod doc;
+pub mod complete;
+pub mod enumerate;
+pub mod coord;
 
 use serde_derive::{Serialize, Deserialize};
 
+pub use self::coord::{Axis, CoordForm, Labels};
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub enum Spec {
     Int { min: Option<i32>, max: Option<i32> },
+    Float {
+        min: Option<f64>,
+        max: Option<f64>,
+        precision: Option<usize>,
+    },
+    Text {
+        min: Option<usize>,
+        max: Option<usize>,
+        chars: CharClass,
+    },
+    Coord(CoordForm),
     Token(String),
-    Enum { values: Vec<String>, exact: bool },
+    Enum {
+        values: Vec<String>,
+        exact: bool,
+        /// Other spellings for each value, `aliases[i]` being for `values[i]`.
+        #[serde(default)]
+        aliases: Vec<Vec<String>>,
+    },
     OneOf(Vec<Spec>),
     Chain(Vec<Spec>),
     Many {
@@ -25,3 +47,30 @@ pub enum Spec {
     Player,
     Space,
 }
+
+/// The characters allowed in free text.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+pub enum CharClass {
+    Any,
+    Alphabetic,
+    Alphanumeric,
+}
+
+impl CharClass {
+    pub fn allows(self, c: char) -> bool {
+        match self {
+            CharClass::Any => true,
+            CharClass::Alphabetic => c.is_alphabetic(),
+            CharClass::Alphanumeric => c.is_alphanumeric(),
+        }
+    }
+
+    /// What the text is called in expected lists and docs.
+    pub fn noun(self) -> &'static str {
+        match self {
+            CharClass::Any => "text",
+            CharClass::Alphabetic => "word",
+            CharClass::Alphanumeric => "letters and numbers",
+        }
+    }
+}
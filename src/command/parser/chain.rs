@@ -1,6 +1,6 @@
 use std::marker::PhantomData;
 
-use crate::command::parser::{Output, Parser};
+use crate::command::parser::{offset_error, Output, Parser};
 use crate::command::Spec as CommandSpec;
 use crate::errors::*;
 
@@ -15,7 +15,9 @@ where
     PB: Parser<B>,
 {
     let lhs = a.parse(input, names)?;
-    let rhs = b.parse(lhs.remaining, names)?;
+    let rhs = b
+        .parse(lhs.remaining, names)
+        .map_err(|e| offset_error(e, lhs.consumed.len()))?;
     let consumed = lhs.consumed.len() + rhs.consumed.len();
     Ok(Output {
         value: (lhs.value, rhs.value),
@@ -24,184 +26,249 @@ where
     })
 }
 
-pub struct Chain2<A, B, PA, PB>
-where
-    PA: Parser<A>,
-    PB: Parser<B>,
-{
-    pub a: PA,
-    pub b: PB,
-    a_type: PhantomData<A>,
-    b_type: PhantomData<B>,
-}
-
-impl<A, B, PA, PB> Chain2<A, B, PA, PB>
-where
-    PA: Parser<A>,
-    PB: Parser<B>,
-{
-    pub fn new(a: PA, b: PB) -> Self {
-        Self {
-            a: a,
-            b: b,
-            a_type: PhantomData,
-            b_type: PhantomData,
+/// Define a chain of parsers of a given arity, which parses each in turn and produces a flat
+/// tuple of their values and a single `Spec::Chain`.
+macro_rules! chain_parser {
+    ($name:ident, $first:ident, $(($field:ident, $type_field:ident, $t:ident, $p:ident)),+) => {
+        pub struct $name<$($t),+, $($p),+>
+        where
+            $($p: Parser<$t>),+
+        {
+            $(pub $field: $p,)+
+            $($type_field: PhantomData<$t>,)+
         }
-    }
-}
-
-impl<A, B, PA, PB> Parser<(A, B)> for Chain2<A, B, PA, PB>
-where
-    PA: Parser<A>,
-    PB: Parser<B>,
-{
-    fn parse<'a>(&self, input: &'a str, names: &[String]) -> Result<Output<'a, (A, B)>, GameError> {
-        chain_2(&self.a, &self.b, input, names)
-    }
 
-    fn expected(&self, names: &[String]) -> Vec<String> {
-        self.a.expected(names)
-    }
-
-    fn to_spec(&self) -> CommandSpec {
-        CommandSpec::Chain(vec![self.a.to_spec(), self.b.to_spec()])
-    }
-}
-
-pub struct Chain3<A, B, C, PA, PB, PC>
-where
-    PA: Parser<A>,
-    PB: Parser<B>,
-    PC: Parser<C>,
-{
-    pub a: PA,
-    pub b: PB,
-    pub c: PC,
-    a_type: PhantomData<A>,
-    b_type: PhantomData<B>,
-    c_type: PhantomData<C>,
-}
-
-impl<A, B, C, PA, PB, PC> Chain3<A, B, C, PA, PB, PC>
-where
-    PA: Parser<A>,
-    PB: Parser<B>,
-    PC: Parser<C>,
-{
-    pub fn new(a: PA, b: PB, c: PC) -> Self {
-        Self {
-            a: a,
-            b: b,
-            c: c,
-            a_type: PhantomData,
-            b_type: PhantomData,
-            c_type: PhantomData,
+        impl<$($t),+, $($p),+> $name<$($t),+, $($p),+>
+        where
+            $($p: Parser<$t>),+
+        {
+            #[allow(clippy::too_many_arguments)]
+            pub fn new($($field: $p),+) -> Self {
+                Self {
+                    $($field,)+
+                    $($type_field: PhantomData,)+
+                }
+            }
         }
-    }
-}
 
-impl<A, B, C, PA, PB, PC> Parser<(A, B, C)> for Chain3<A, B, C, PA, PB, PC>
-where
-    PA: Parser<A>,
-    PB: Parser<B>,
-    PC: Parser<C>,
-{
-    fn parse<'a>(
-        &self,
-        input: &'a str,
-        names: &[String],
-    ) -> Result<Output<'a, (A, B, C)>, GameError> {
-        let head = self.a.parse(input, names)?;
-        let tail = chain_2(&self.b, &self.c, head.remaining, names)?;
-        let consumed = head.consumed.len() + tail.consumed.len();
-        Ok(Output {
-            value: (head.value, tail.value.0, tail.value.1),
-            consumed: &input[..consumed],
-            remaining: &input[consumed..],
-        })
-    }
-
-    fn expected(&self, names: &[String]) -> Vec<String> {
-        self.a.expected(names)
-    }
-
-    fn to_spec(&self) -> CommandSpec {
-        CommandSpec::Chain(vec![self.a.to_spec(), self.b.to_spec(), self.c.to_spec()])
-    }
-}
+        impl<$($t),+, $($p),+> Parser<($($t),+)> for $name<$($t),+, $($p),+>
+        where
+            $($p: Parser<$t>),+
+        {
+            fn parse<'a>(
+                &self,
+                input: &'a str,
+                names: &[String],
+            ) -> Result<Output<'a, ($($t),+)>, GameError> {
+                let mut consumed = 0;
+                let value = ($({
+                    let output = self.$field
+                        .parse(&input[consumed..], names)
+                        .map_err(|e| offset_error(e, consumed))?;
+                    consumed += output.consumed.len();
+                    output.value
+                }),+);
+                Ok(Output {
+                    value,
+                    consumed: &input[..consumed],
+                    remaining: &input[consumed..],
+                })
+            }
 
-pub struct Chain4<A, B, C, D, PA, PB, PC, PD>
-where
-    PA: Parser<A>,
-    PB: Parser<B>,
-    PC: Parser<C>,
-    PD: Parser<D>,
-{
-    pub a: PA,
-    pub b: PB,
-    pub c: PC,
-    pub d: PD,
-    a_type: PhantomData<A>,
-    b_type: PhantomData<B>,
-    c_type: PhantomData<C>,
-    d_type: PhantomData<D>,
-}
+            fn expected(&self, names: &[String]) -> Vec<String> {
+                self.$first.expected(names)
+            }
 
-impl<A, B, C, D, PA, PB, PC, PD> Chain4<A, B, C, D, PA, PB, PC, PD>
-where
-    PA: Parser<A>,
-    PB: Parser<B>,
-    PC: Parser<C>,
-    PD: Parser<D>,
-{
-    pub fn new(a: PA, b: PB, c: PC, d: PD) -> Self {
-        Self {
-            a: a,
-            b: b,
-            c: c,
-            d: d,
-            a_type: PhantomData,
-            b_type: PhantomData,
-            c_type: PhantomData,
-            d_type: PhantomData,
+            fn to_spec(&self) -> CommandSpec {
+                CommandSpec::Chain(vec![$(self.$field.to_spec()),+])
+            }
         }
-    }
+    };
 }
 
-impl<A, B, C, D, PA, PB, PC, PD> Parser<(A, B, C, D)> for Chain4<A, B, C, D, PA, PB, PC, PD>
-where
-    PA: Parser<A>,
-    PB: Parser<B>,
-    PC: Parser<C>,
-    PD: Parser<D>,
-{
-    fn parse<'a>(
-        &self,
-        input: &'a str,
-        names: &[String],
-    ) -> Result<Output<'a, (A, B, C, D)>, GameError> {
-        let head = chain_2(&self.a, &self.b, input, names)?;
-        let tail = chain_2(&self.c, &self.d, head.remaining, names)?;
-        let consumed = head.consumed.len() + tail.consumed.len();
-        Ok(Output {
-            value: (head.value.0, head.value.1, tail.value.0, tail.value.1),
-            consumed: &input[..consumed],
-            remaining: &input[consumed..],
-        })
-    }
-
-    fn expected(&self, names: &[String]) -> Vec<String> {
-        self.a.expected(names)
-    }
+chain_parser!(Chain2, a, (a, a_type, A, PA), (b, b_type, B, PB));
+chain_parser!(Chain3, a, (a, a_type, A, PA), (b, b_type, B, PB), (c, c_type, C, PC));
+chain_parser!(
+    Chain4,
+    a,
+    (a, a_type, A, PA),
+    (b, b_type, B, PB),
+    (c, c_type, C, PC),
+    (d, d_type, D, PD)
+);
+chain_parser!(
+    Chain5,
+    a,
+    (a, a_type, A, PA),
+    (b, b_type, B, PB),
+    (c, c_type, C, PC),
+    (d, d_type, D, PD),
+    (e, e_type, E, PE)
+);
+chain_parser!(
+    Chain6,
+    a,
+    (a, a_type, A, PA),
+    (b, b_type, B, PB),
+    (c, c_type, C, PC),
+    (d, d_type, D, PD),
+    (e, e_type, E, PE),
+    (f, f_type, F, PF)
+);
+chain_parser!(
+    Chain7,
+    a,
+    (a, a_type, A, PA),
+    (b, b_type, B, PB),
+    (c, c_type, C, PC),
+    (d, d_type, D, PD),
+    (e, e_type, E, PE),
+    (f, f_type, F, PF),
+    (g, g_type, G, PG)
+);
+chain_parser!(
+    Chain8,
+    a,
+    (a, a_type, A, PA),
+    (b, b_type, B, PB),
+    (c, c_type, C, PC),
+    (d, d_type, D, PD),
+    (e, e_type, E, PE),
+    (f, f_type, F, PF),
+    (g, g_type, G, PG),
+    (h, h_type, H, PH)
+);
+chain_parser!(
+    Chain9,
+    a,
+    (a, a_type, A, PA),
+    (b, b_type, B, PB),
+    (c, c_type, C, PC),
+    (d, d_type, D, PD),
+    (e, e_type, E, PE),
+    (f, f_type, F, PF),
+    (g, g_type, G, PG),
+    (h, h_type, H, PH),
+    (i, i_type, I, PI)
+);
+chain_parser!(
+    Chain10,
+    a,
+    (a, a_type, A, PA),
+    (b, b_type, B, PB),
+    (c, c_type, C, PC),
+    (d, d_type, D, PD),
+    (e, e_type, E, PE),
+    (f, f_type, F, PF),
+    (g, g_type, G, PG),
+    (h, h_type, H, PH),
+    (i, i_type, I, PI),
+    (j, j_type, J, PJ)
+);
+chain_parser!(
+    Chain11,
+    a,
+    (a, a_type, A, PA),
+    (b, b_type, B, PB),
+    (c, c_type, C, PC),
+    (d, d_type, D, PD),
+    (e, e_type, E, PE),
+    (f, f_type, F, PF),
+    (g, g_type, G, PG),
+    (h, h_type, H, PH),
+    (i, i_type, I, PI),
+    (j, j_type, J, PJ),
+    (k, k_type, K, PK)
+);
+chain_parser!(
+    Chain12,
+    a,
+    (a, a_type, A, PA),
+    (b, b_type, B, PB),
+    (c, c_type, C, PC),
+    (d, d_type, D, PD),
+    (e, e_type, E, PE),
+    (f, f_type, F, PF),
+    (g, g_type, G, PG),
+    (h, h_type, H, PH),
+    (i, i_type, I, PI),
+    (j, j_type, J, PJ),
+    (k, k_type, K, PK),
+    (l, l_type, L, PL)
+);
 
-    fn to_spec(&self) -> CommandSpec {
-        CommandSpec::Chain(vec![
-            self.a.to_spec(),
-            self.b.to_spec(),
-            self.c.to_spec(),
-            self.d.to_spec(),
-        ])
-    }
+/// Chain any number of parsers from 2 to 12, so `chain!(a, b, c)` is `Chain3::new(a, b, c)`.
+#[macro_export]
+macro_rules! chain {
+    ($a:expr, $b:expr $(,)?) => {
+        $crate::command::parser::Chain2::new($a, $b)
+    };
+    ($a:expr, $b:expr, $c:expr $(,)?) => {
+        $crate::command::parser::Chain3::new($a, $b, $c)
+    };
+    ($a:expr, $b:expr, $c:expr, $d:expr $(,)?) => {
+        $crate::command::parser::Chain4::new($a, $b, $c, $d)
+    };
+    ($a:expr, $b:expr, $c:expr, $d:expr, $e:expr $(,)?) => {
+        $crate::command::parser::Chain5::new($a, $b, $c, $d, $e)
+    };
+    ($a:expr, $b:expr, $c:expr, $d:expr, $e:expr, $f:expr $(,)?) => {
+        $crate::command::parser::Chain6::new($a, $b, $c, $d, $e, $f)
+    };
+    ($a:expr, $b:expr, $c:expr, $d:expr, $e:expr, $f:expr, $g:expr $(,)?) => {
+        $crate::command::parser::Chain7::new($a, $b, $c, $d, $e, $f, $g)
+    };
+    ($a:expr, $b:expr, $c:expr, $d:expr, $e:expr, $f:expr, $g:expr, $h:expr $(,)?) => {
+        $crate::command::parser::Chain8::new($a, $b, $c, $d, $e, $f, $g, $h)
+    };
+    ($a:expr, $b:expr, $c:expr, $d:expr, $e:expr, $f:expr, $g:expr, $h:expr, $i:expr $(,)?) => {
+        $crate::command::parser::Chain9::new($a, $b, $c, $d, $e, $f, $g, $h, $i)
+    };
+    (
+        $a:expr,
+        $b:expr,
+        $c:expr,
+        $d:expr,
+        $e:expr,
+        $f:expr,
+        $g:expr,
+        $h:expr,
+        $i:expr,
+        $j:expr $(,)?
+    ) => {
+        $crate::command::parser::Chain10::new($a, $b, $c, $d, $e, $f, $g, $h, $i, $j)
+    };
+    (
+        $a:expr,
+        $b:expr,
+        $c:expr,
+        $d:expr,
+        $e:expr,
+        $f:expr,
+        $g:expr,
+        $h:expr,
+        $i:expr,
+        $j:expr,
+        $k:expr $(,)?
+    ) => {
+        $crate::command::parser::Chain11::new($a, $b, $c, $d, $e, $f, $g, $h, $i, $j, $k)
+    };
+    (
+        $a:expr,
+        $b:expr,
+        $c:expr,
+        $d:expr,
+        $e:expr,
+        $f:expr,
+        $g:expr,
+        $h:expr,
+        $i:expr,
+        $j:expr,
+        $k:expr,
+        $l:expr $(,)?
+    ) => {
+        $crate::command::parser::Chain12::new($a, $b, $c, $d, $e, $f, $g, $h, $i, $j, $k, $l)
+    };
 }
 
 #[cfg(test)]
@@ -231,4 +298,41 @@ mod tests {
                 .expect("expected '123egg  chairs' to parse",)
         )
     }
+
+    #[test]
+    fn long_chains_are_flat() {
+        use crate::command::parser::{AfterSpace, Int, Player, Token};
+        let parser = chain!(
+            Token::new("move"),
+            AfterSpace::new(Player {}),
+            AfterSpace::new(Token::new("from")),
+            AfterSpace::new(Int::any()),
+            AfterSpace::new(Token::new("to")),
+            AfterSpace::new(Int::bounded(1, 5)),
+        );
+        let names = vec!["mick".to_string(), "steve".to_string()];
+        let output = parser
+            .parse("move steve from 3 to 4 now", &names)
+            .expect("expected 'move steve from 3 to 4 now' to parse");
+        assert_eq!(
+            (
+                "move".to_string(),
+                1,
+                "from".to_string(),
+                3,
+                "to".to_string(),
+                4,
+            ),
+            output.value
+        );
+        assert_eq!(" now", output.remaining);
+        match parser.to_spec() {
+            CommandSpec::Chain(specs) => assert_eq!(6, specs.len()),
+            spec => panic!("expected a chain spec, got {:?}", spec),
+        }
+        match parser.parse("move steve from 3 to 9", &names) {
+            Err(GameError::Parse { offset, .. }) => assert_eq!(21, offset),
+            result => panic!("expected a parse error, got {:?}", result.map(|o| o.value)),
+        }
+    }
 }
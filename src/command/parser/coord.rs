@@ -0,0 +1,278 @@
+use std::mem;
+
+use crate::command::parser::{Int, Output, Parser};
+use crate::command::{Axis, CoordForm, Spec as CommandSpec};
+use crate::errors::GameError;
+
+const SEPARATOR_NEEDED: &str = "grid coordinates need a separator when both axes use the same \
+                                kind of labels";
+
+/// A position on a grid board, parsed as a zero based column and row.
+pub struct Coord {
+    pub columns: Axis,
+    pub rows: Axis,
+    /// Required between the column and the row, spaces are allowed around it. Without a
+    /// separator the column and row labels must be of different kinds.
+    pub separator: Option<String>,
+}
+
+/// Whether the column label would run into the row label without a separator between them.
+fn same_kind(columns: &Axis, rows: &Axis) -> bool {
+    mem::discriminant(&columns.labels) == mem::discriminant(&rows.labels)
+}
+
+/// A position on a hexagonal board in axial coordinates, parsed as q and r.
+pub struct HexCoord {
+    pub radius: usize,
+}
+
+impl Coord {
+    /// # Panics
+    ///
+    /// Panics if there is no separator and both axes use the same kind of labels, as the column
+    /// would consume the row.
+    pub fn new(columns: Axis, rows: Axis, separator: Option<String>) -> Self {
+        assert!(separator.is_some() || !same_kind(&columns, &rows), "{}", SEPARATOR_NEEDED);
+        Self {
+            columns,
+            rows,
+            separator,
+        }
+    }
+
+    /// Letter columns directly followed by number rows, like "c12".
+    pub fn chess(columns: usize, rows: usize) -> Self {
+        Self::new(Axis::letters(columns), Axis::numbers(rows), None)
+    }
+
+    /// Column and row numbers separated by a comma, like "3,4".
+    pub fn pair(columns: usize, rows: usize) -> Self {
+        Self::new(
+            Axis::numbers(columns),
+            Axis::numbers(rows),
+            Some(",".to_string()),
+        )
+    }
+
+    fn form(&self) -> CoordForm {
+        CoordForm::Grid {
+            columns: self.columns,
+            rows: self.rows,
+            separator: self.separator.to_owned(),
+        }
+    }
+}
+
+impl HexCoord {
+    pub fn new(radius: usize) -> Self {
+        Self { radius }
+    }
+}
+
+fn skip_space(input: &str, pos: usize) -> usize {
+    input.len() - input[pos..].trim_start().len()
+}
+
+/// A `CoordForm` parses positions generically, producing the column and row for grids, or q and r
+/// for axial coordinates.
+impl Parser<(i32, i32)> for CoordForm {
+    fn parse<'a>(
+        &self,
+        input: &'a str,
+        names: &[String],
+    ) -> Result<Output<'a, (i32, i32)>, GameError> {
+        let error = |message: Option<String>| GameError::Parse {
+            message,
+            expected: self.expected(names),
+            offset: 0,
+            suggestions: vec![],
+        };
+        let (value, len) = match *self {
+            CoordForm::Grid {
+                ref columns,
+                ref rows,
+                ref separator,
+            } => {
+                if separator.is_none() && same_kind(columns, rows) {
+                    return Err(GameError::internal(SEPARATOR_NEEDED));
+                }
+                let column_len = columns.labels.label_len(input);
+                let mut pos = column_len;
+                if let Some(ref separator) = *separator {
+                    pos = skip_space(input, pos);
+                    if !input[pos..].starts_with(separator.as_str()) {
+                        return Err(error(None));
+                    }
+                    pos = skip_space(input, pos + separator.len());
+                }
+                let row_len = rows.labels.label_len(&input[pos..]);
+                if column_len == 0 || row_len == 0 {
+                    return Err(error(None));
+                }
+                let end = pos + row_len;
+                let index = |axis: &Axis, label: &str| {
+                    axis.labels.index(label).filter(|&i| i < axis.size)
+                };
+                match (
+                    index(columns, &input[..column_len]),
+                    index(rows, &input[pos..end]),
+                ) {
+                    (Some(x), Some(y)) => ((x as i32, y as i32), end),
+                    _ => {
+                        return Err(error(Some(format!("{} is off the board", &input[..end]))));
+                    }
+                }
+            }
+            CoordForm::Axial { radius } => {
+                let q = Int::any().parse(input, names).map_err(|_| error(None))?;
+                let mut pos = skip_space(input, q.consumed.len());
+                if !input[pos..].starts_with(',') {
+                    return Err(error(None));
+                }
+                pos = skip_space(input, pos + 1);
+                let r = Int::any()
+                    .parse(&input[pos..], names)
+                    .map_err(|_| error(None))?;
+                let end = pos + r.consumed.len();
+                let radius = radius as i64;
+                let (q, r) = (q.value, r.value);
+                if i64::from(q).abs() > radius
+                    || i64::from(r).abs() > radius
+                    || (i64::from(q) + i64::from(r)).abs() > radius
+                {
+                    return Err(error(Some(format!("{} is off the board", &input[..end]))));
+                }
+                ((q, r), end)
+            }
+        };
+        Ok(Output {
+            value,
+            consumed: &input[..len],
+            remaining: &input[len..],
+        })
+    }
+
+    fn expected(&self, _names: &[String]) -> Vec<String> {
+        vec![format!("coordinate {}", self.describe())]
+    }
+
+    fn to_spec(&self) -> CommandSpec {
+        CommandSpec::Coord(self.to_owned())
+    }
+}
+
+impl Parser<(usize, usize)> for Coord {
+    fn parse<'a>(
+        &self,
+        input: &'a str,
+        names: &[String],
+    ) -> Result<Output<'a, (usize, usize)>, GameError> {
+        let output = self.form().parse(input, names)?;
+        Ok(Output {
+            value: (output.value.0 as usize, output.value.1 as usize),
+            consumed: output.consumed,
+            remaining: output.remaining,
+        })
+    }
+
+    fn expected(&self, names: &[String]) -> Vec<String> {
+        self.form().expected(names)
+    }
+
+    fn to_spec(&self) -> CommandSpec {
+        self.form().to_spec()
+    }
+}
+
+impl Parser<(i32, i32)> for HexCoord {
+    fn parse<'a>(
+        &self,
+        input: &'a str,
+        names: &[String],
+    ) -> Result<Output<'a, (i32, i32)>, GameError> {
+        CoordForm::axial(self.radius).parse(input, names)
+    }
+
+    fn expected(&self, names: &[String]) -> Vec<String> {
+        CoordForm::axial(self.radius).expected(names)
+    }
+
+    fn to_spec(&self) -> CommandSpec {
+        CoordForm::axial(self.radius).to_spec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::Labels;
+
+    fn parse<T, P: Parser<T>>(parser: &P, input: &str) -> (T, String) {
+        let output = parser
+            .parse(input, &[])
+            .unwrap_or_else(|e| panic!("expected '{}' to parse: {}", input, e));
+        (output.value, output.remaining.to_string())
+    }
+
+    fn message<T, P: Parser<T>>(parser: &P, input: &str) -> String {
+        match parser.parse(input, &[]) {
+            Ok(_) => panic!("expected '{}' to produce an error", input),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn coord_parser_works() {
+        let chess = Coord::chess(8, 8);
+        assert_eq!(((2, 2), " to d4".to_string()), parse(&chess, "c3 to d4"));
+        assert_eq!(((7, 0), "".to_string()), parse(&chess, "H1"));
+        assert_eq!("i1 is off the board, expected coordinate a1-h8", message(&chess, "i1"));
+        assert_eq!("a9 is off the board, expected coordinate a1-h8", message(&chess, "a9"));
+        assert_eq!("expected coordinate a1-h8", message(&chess, "a"));
+        let pair = Coord::pair(10, 5);
+        assert_eq!(((2, 3), "".to_string()), parse(&pair, "3,4"));
+        assert_eq!(((9, 0), " x".to_string()), parse(&pair, "10 , 1 x"));
+        message(&pair, "3 4");
+        let zero_based = Coord::new(
+            Axis::new(3, Labels::Numbers { start: 0 }),
+            Axis::letters(30),
+            None,
+        );
+        assert_eq!(((0, 27), "".to_string()), parse(&zero_based, "0ab"));
+        let form = CoordForm::Grid {
+            columns: Axis::numbers(3),
+            rows: Axis::numbers(3),
+            separator: None,
+        };
+        match form.parse("12", &[]) {
+            Err(GameError::Internal { .. }) => {}
+            r => panic!("expected an internal error, got {:?}", r.map(|o| o.value)),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "grid coordinates need a separator")]
+    fn coord_needs_separator_for_same_labels() {
+        Coord::new(Axis::letters(3), Axis::letters(3), None);
+    }
+
+    #[test]
+    fn hex_coord_parser_works() {
+        let hex = HexCoord::new(2);
+        assert_eq!(((-1, 2), "".to_string()), parse(&hex, "-1,2"));
+        assert_eq!(((0, 0), " go".to_string()), parse(&hex, "0, 0 go"));
+        assert_eq!(
+            "2,1 is off the board, expected coordinate q,r within 2 of 0,0",
+            message(&hex, "2,1")
+        );
+        message(&hex, "1");
+        let inputs = hex
+            .to_spec()
+            .enumerate(&[], 100)
+            .expect("expected board to be enumerable");
+        assert_eq!(19, inputs.len());
+        for input in &inputs {
+            parse(&hex, input);
+        }
+    }
+}
@@ -1,14 +1,22 @@
 use unicase::UniCase;
 
-use std::marker::PhantomData;
 use std::collections::HashSet;
+use std::marker::PhantomData;
 use std::fmt::Display;
 
 pub mod chain;
+pub mod coord;
+pub mod spec;
+pub mod suggest;
 
+use crate::bot::spec_bot;
 use crate::errors::GameError;
-use crate::command::Spec as CommandSpec;
+use crate::command::{CharClass, Spec as CommandSpec};
+use crate::game::GameRng;
+use self::suggest::{has_suggestions, name_unknown, unknown_error};
 pub use self::chain::*;
+pub use self::coord::{Coord, HexCoord};
+pub use self::spec::SpecValue;
 
 const MANY_DELIM: &str = ",";
 
@@ -25,6 +33,53 @@ pub trait Parser<T> {
     fn to_spec(&self) -> CommandSpec;
 }
 
+impl<T, P: Parser<T> + ?Sized> Parser<T> for &P {
+    fn parse<'a>(&self, input: &'a str, names: &[String]) -> Result<Output<'a, T>, GameError> {
+        (**self).parse(input, names)
+    }
+
+    fn expected(&self, names: &[String]) -> Vec<String> {
+        (**self).expected(names)
+    }
+
+    fn to_spec(&self) -> CommandSpec {
+        (**self).to_spec()
+    }
+}
+
+impl<T, P: Parser<T> + ?Sized> Parser<T> for Box<P> {
+    fn parse<'a>(&self, input: &'a str, names: &[String]) -> Result<Output<'a, T>, GameError> {
+        (**self).parse(input, names)
+    }
+
+    fn expected(&self, names: &[String]) -> Vec<String> {
+        (**self).expected(names)
+    }
+
+    fn to_spec(&self) -> CommandSpec {
+        (**self).to_spec()
+    }
+}
+
+/// Move the offset of a parse error along by `by`, for when the input given to a child parser
+/// started part way through the input.
+pub fn offset_error(error: GameError, by: usize) -> GameError {
+    match error {
+        GameError::Parse {
+            message,
+            expected,
+            offset,
+            suggestions,
+        } => GameError::Parse {
+            message,
+            expected,
+            offset: offset + by,
+            suggestions,
+        },
+        e => e,
+    }
+}
+
 pub struct Token {
     pub token: String,
 }
@@ -44,10 +99,14 @@ impl Parser<String> for Token {
     fn parse<'a>(&self, input: &'a str, names: &[String]) -> Result<Output<'a, String>, GameError> {
         let t_len = self.token.len();
         if input.len() < self.token.len() || UniCase(&input[..t_len]) != UniCase(&self.token) {
+            if let Some(e) = unknown_error(input, Some(self.token.as_ref()), self.expected(names)) {
+                return Err(e);
+            }
             return Err(GameError::Parse {
                 message: None,
                 expected: self.expected(names),
                 offset: 0,
+                suggestions: vec![],
             });
         }
         Ok(Output {
@@ -132,6 +191,7 @@ impl Parser<i32> for Int {
                 message: None,
                 expected: self.expected(names),
                 offset: 0,
+                suggestions: vec![],
             });
         }
         let consumed = &input[..consumed_count];
@@ -140,6 +200,7 @@ impl Parser<i32> for Int {
                 message: Some(format!("failed to parse '{}'", consumed)),
                 expected: self.expected(names),
                 offset: 0,
+                suggestions: vec![],
             }
         })?;
         if let Some(min) = self.min {
@@ -148,6 +209,7 @@ impl Parser<i32> for Int {
                     message: Some(format!("{} is too low", value)),
                     expected: self.expected(names),
                     offset: 0,
+                    suggestions: vec![],
                 });
             }
         }
@@ -157,6 +219,7 @@ impl Parser<i32> for Int {
                     message: Some(format!("{} is too high", value)),
                     expected: self.expected(names),
                     offset: 0,
+                    suggestions: vec![],
                 });
             }
         }
@@ -179,6 +242,258 @@ impl Parser<i32> for Int {
     }
 }
 
+pub struct Float {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// The maximum number of decimal places.
+    pub precision: Option<usize>,
+}
+
+impl Float {
+    pub fn any() -> Self {
+        Float {
+            min: None,
+            max: None,
+            precision: None,
+        }
+    }
+
+    pub fn not_negative() -> Self {
+        Float {
+            min: Some(0.0),
+            max: None,
+            precision: None,
+        }
+    }
+
+    pub fn bounded(min: f64, max: f64) -> Self {
+        Float {
+            min: Some(min),
+            max: Some(max),
+            precision: None,
+        }
+    }
+
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    fn expected_output(&self) -> String {
+        let number = match self.precision {
+            Some(0) => "whole number".to_string(),
+            Some(1) => "number with up to 1 decimal place".to_string(),
+            Some(p) => format!("number with up to {} decimal places", p),
+            None => "number".to_string(),
+        };
+        match (self.min, self.max) {
+            (None, None) => number,
+            (Some(min), None) => format!("{} {} or higher", number, min),
+            (None, Some(max)) => format!("{} {} or lower", number, max),
+            (Some(min), Some(max)) => format!("{} between {} and {}", number, min, max),
+        }
+    }
+}
+
+/// The length of a number at the start of the input, and the length of its fractional part. A
+/// decimal point is only included if digits follow it.
+fn float_len(input: &str) -> Option<(usize, usize)> {
+    let bytes = input.as_bytes();
+    let digits_from = |from: usize| {
+        bytes[from.min(bytes.len())..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count()
+    };
+    let sign = if bytes.first() == Some(&b'-') { 1 } else { 0 };
+    let whole = digits_from(sign);
+    let point = sign + whole;
+    let fraction = if bytes.get(point) == Some(&b'.') {
+        digits_from(point + 1)
+    } else {
+        0
+    };
+    match (whole, fraction) {
+        (0, 0) => None,
+        (_, 0) => Some((point, 0)),
+        _ => Some((point + 1 + fraction, fraction)),
+    }
+}
+
+impl Parser<f64> for Float {
+    fn parse<'a>(&self, input: &'a str, names: &[String]) -> Result<Output<'a, f64>, GameError> {
+        let error = |message: Option<String>| GameError::Parse {
+            message,
+            expected: self.expected(names),
+            offset: 0,
+            suggestions: vec![],
+        };
+        let (len, fraction) = float_len(input).ok_or_else(|| error(None))?;
+        let consumed = &input[..len];
+        let value: f64 = consumed
+            .parse()
+            .map_err(|_| error(Some(format!("failed to parse '{}'", consumed))))?;
+        if let Some(precision) = self.precision {
+            if fraction > precision {
+                return Err(error(Some(format!(
+                    "{} has too many decimal places",
+                    consumed
+                ))));
+            }
+        }
+        if let Some(min) = self.min {
+            if value < min {
+                return Err(error(Some(format!("{} is too low", consumed))));
+            }
+        }
+        if let Some(max) = self.max {
+            if value > max {
+                return Err(error(Some(format!("{} is too high", consumed))));
+            }
+        }
+        Ok(Output {
+            value,
+            consumed,
+            remaining: &input[len..],
+        })
+    }
+
+    fn expected(&self, _names: &[String]) -> Vec<String> {
+        vec![self.expected_output()]
+    }
+
+    fn to_spec(&self) -> CommandSpec {
+        CommandSpec::Float {
+            min: self.min,
+            max: self.max,
+            precision: self.precision,
+        }
+    }
+}
+
+/// Free text, either a bare word ending at whitespace or a comma, or a string in double or single
+/// quotes. Inside quotes a backslash escapes the next character. Lengths are counted in chars.
+pub struct Text {
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+    pub chars: CharClass,
+}
+
+impl Text {
+    pub fn any() -> Self {
+        Text {
+            min: None,
+            max: None,
+            chars: CharClass::Any,
+        }
+    }
+
+    pub fn bounded(min: usize, max: usize) -> Self {
+        Text {
+            min: Some(min),
+            max: Some(max),
+            chars: CharClass::Any,
+        }
+    }
+
+    pub fn chars(mut self, chars: CharClass) -> Self {
+        self.chars = chars;
+        self
+    }
+
+    fn expected_output(&self) -> String {
+        let noun = self.chars.noun();
+        match (self.min, self.max) {
+            (None, None) => noun.to_string(),
+            (Some(min), None) => format!("{} of at least {} characters", noun, min),
+            (None, Some(max)) => format!("{} of up to {} characters", noun, max),
+            (Some(min), Some(max)) if min == max => format!("{} of {} characters", noun, min),
+            (Some(min), Some(max)) => format!("{} of {} to {} characters", noun, min, max),
+        }
+    }
+
+    /// The unescaped contents of a quoted string at the start of the input, and the length of
+    /// the input it used including the quotes.
+    fn quoted(&self, input: &str) -> Option<Result<(String, usize), String>> {
+        let quote = match input.chars().next() {
+            Some(c) if c == '"' || c == '\'' => c,
+            _ => return None,
+        };
+        let mut value = String::new();
+        let mut escaped = false;
+        for (i, c) in input.char_indices().skip(1) {
+            if escaped {
+                value.push(c);
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == quote {
+                return Some(Ok((value, i + c.len_utf8())));
+            } else {
+                value.push(c);
+            }
+        }
+        Some(Err(format!("missing closing {}", quote)))
+    }
+}
+
+impl Parser<String> for Text {
+    fn parse<'a>(&self, input: &'a str, names: &[String]) -> Result<Output<'a, String>, GameError> {
+        let error = |message: Option<String>| GameError::Parse {
+            message,
+            expected: self.expected(names),
+            offset: 0,
+            suggestions: vec![],
+        };
+        let (value, len) = match self.quoted(input) {
+            Some(result) => result.map_err(|message| error(Some(message)))?,
+            None => {
+                let len = input
+                    .find(|c: char| c.is_whitespace() || MANY_DELIM.contains(c))
+                    .unwrap_or(input.len());
+                if len == 0 {
+                    return Err(error(None));
+                }
+                (input[..len].to_string(), len)
+            }
+        };
+        if !value.chars().all(|c| self.chars.allows(c)) {
+            return Err(error(Some(format!(
+                "'{}' can only contain {}",
+                value,
+                match self.chars {
+                    CharClass::Alphabetic => "letters",
+                    _ => "letters and numbers",
+                }
+            ))));
+        }
+        let chars = value.chars().count();
+        if self.min.map(|min| chars < min).unwrap_or(false) {
+            return Err(error(Some(format!("'{}' is too short", value))));
+        }
+        if self.max.map(|max| chars > max).unwrap_or(false) {
+            return Err(error(Some(format!("'{}' is too long", value))));
+        }
+        Ok(Output {
+            value,
+            consumed: &input[..len],
+            remaining: &input[len..],
+        })
+    }
+
+    fn expected(&self, _names: &[String]) -> Vec<String> {
+        vec![self.expected_output()]
+    }
+
+    fn to_spec(&self) -> CommandSpec {
+        CommandSpec::Text {
+            min: self.min,
+            max: self.max,
+            chars: self.chars,
+        }
+    }
+}
+
 pub struct Map<T, O, F, TP>
 where
     F: Fn(T) -> O,
@@ -228,6 +543,112 @@ where
     }
 }
 
+/// The most branches `AndThen` lists in its spec, and how many inputs are sampled to find what
+/// can follow the first parser when its inputs can't be listed.
+const AND_THEN_BRANCHES: usize = 100;
+/// How many placeholder player names are used when sampling the first parser of `AndThen`.
+const AND_THEN_PLAYERS: usize = 8;
+
+/// Parse a value, then choose the parser for what follows from it, producing both values. Useful
+/// when the arguments of a command depend on an earlier choice, such as the card being played.
+pub struct AndThen<A, B, PA, PB, F>
+where
+    PA: Parser<A>,
+    PB: Parser<B>,
+    F: Fn(&A) -> PB,
+{
+    pub parser: PA,
+    pub and_then: F,
+    a_type: PhantomData<A>,
+    b_type: PhantomData<B>,
+}
+
+impl<A, B, PA, PB, F> AndThen<A, B, PA, PB, F>
+where
+    PA: Parser<A>,
+    PB: Parser<B>,
+    F: Fn(&A) -> PB,
+{
+    pub fn new(parser: PA, and_then: F) -> Self {
+        Self {
+            parser,
+            and_then,
+            a_type: PhantomData,
+            b_type: PhantomData,
+        }
+    }
+}
+
+impl<A, B, PA, PB, F> Parser<(A, B)> for AndThen<A, B, PA, PB, F>
+where
+    PA: Parser<A>,
+    PB: Parser<B>,
+    F: Fn(&A) -> PB,
+{
+    fn parse<'a>(&self, input: &'a str, names: &[String]) -> Result<Output<'a, (A, B)>, GameError> {
+        let head = self.parser.parse(input, names)?;
+        let tail = (self.and_then)(&head.value)
+            .parse(head.remaining, names)
+            .map_err(|e| offset_error(e, head.consumed.len()))?;
+        let consumed = head.consumed.len() + tail.consumed.len();
+        Ok(Output {
+            value: (head.value, tail.value),
+            consumed: &input[..consumed],
+            remaining: &input[consumed..],
+        })
+    }
+
+    fn expected(&self, names: &[String]) -> Vec<String> {
+        self.parser.expected(names)
+    }
+
+    /// A `OneOf` with a chain of the first parser and each distinct spec which can follow it.
+    /// The first parser is kept whole in every branch so it accepts the same input as when
+    /// parsing. If its inputs can't be listed, they are sampled and the first parser is chained
+    /// with a `OneOf` of the specs which followed them.
+    fn to_spec(&self) -> CommandSpec {
+        let head = self.parser.to_spec();
+        let tail_spec = |input: &str, names: &[String]| {
+            self.parser
+                .parse(input, names)
+                .ok()
+                .map(|output| (self.and_then)(&output.value).to_spec())
+        };
+        let mut tails: Vec<CommandSpec> = vec![];
+        let mut add_tail = |tail: Option<CommandSpec>| match tail {
+            Some(tail) if !tails.contains(&tail) => tails.push(tail),
+            _ => {}
+        };
+        match head.enumerate(&[], AND_THEN_BRANCHES) {
+            Some(ref inputs) if !inputs.is_empty() => {
+                for input in inputs {
+                    add_tail(tail_spec(input, &[]));
+                }
+                return CommandSpec::OneOf(
+                    tails
+                        .into_iter()
+                        .map(|tail| CommandSpec::Chain(vec![head.clone(), tail]))
+                        .collect(),
+                );
+            }
+            _ => {
+                let names = (0..AND_THEN_PLAYERS)
+                    .map(|p| p.to_string())
+                    .collect::<Vec<String>>();
+                let mut rng = GameRng::from_seed(0);
+                for _ in 0..AND_THEN_BRANCHES {
+                    add_tail(tail_spec(&spec_bot::generate(&head, &names, &mut rng), &names));
+                }
+            }
+        }
+        match tails.len() {
+            0 => head,
+            1 => CommandSpec::Chain(vec![head, tails.remove(0)]),
+            _ => CommandSpec::Chain(vec![head, CommandSpec::OneOf(tails)]),
+        }
+    }
+}
+
 pub struct Opt<T, TP>
 where
     TP: Parser<T>,
@@ -347,6 +768,8 @@ where
         }
         let mut first = true;
         let mut offset = 0;
+        // Where parsing stopped and why, used for the error if there aren't enough items.
+        let mut stopped: Option<GameError> = None;
         let delim = Chain2::new(
             Opt::new(Space {}),
             Chain2::new(Token::new(self.delim.to_owned()), Opt::new(Space {})),
@@ -356,7 +779,10 @@ where
             if !first {
                 match delim.parse(&input[offset..], names) {
                     Ok(Output { consumed, .. }) => inner_offset += consumed.len(),
-                    Err(_) => break 'outer,
+                    Err(e) => {
+                        stopped = Some(offset_error(e, offset));
+                        break 'outer;
+                    }
                 };
             } else {
                 first = false;
@@ -373,21 +799,31 @@ where
                         }
                     }
                 }
-                Err(_) => {
+                Err(e) => {
+                    stopped = Some(offset_error(e, inner_offset));
                     break 'outer;
                 }
             };
         }
         if let Some(min) = self.min {
             if parsed.len() < min {
+                let (expected, stopped_at) = match stopped {
+                    // A close match for an unknown item is more useful than the count.
+                    Some(e @ GameError::Parse { .. }) if has_suggestions(&e) => return Err(e),
+                    Some(GameError::Parse {
+                        expected, offset, ..
+                    }) => (expected, offset),
+                    _ => (self.parser.expected(names), offset),
+                };
                 return Err(GameError::Parse {
                     message: Some(format!(
                         "expected at least {} items but could only parse {}",
                         min,
                         parsed.len()
                     )),
-                    expected: vec![],
-                    offset: 0,
+                    expected,
+                    offset: stopped_at,
+                    suggestions: vec![],
                 });
             }
         }
@@ -431,6 +867,7 @@ impl Parser<String> for Space {
                 message: None,
                 expected: self.expected(names),
                 offset: 0,
+                suggestions: vec![],
             });
         }
         Ok(Output {
@@ -485,21 +922,34 @@ impl<T, TP: Parser<T> + ?Sized> Parser<T> for OneOf<T, TP> {
             }
         }
 
-        let error_messages = &errors
-            .iter()
-            .filter_map(|e| {
-                if let GameError::Parse { ref message, .. } = *e {
-                    message.to_owned()
-                } else {
-                    None
+        let mut error_messages: Vec<String> = vec![];
+        for e in &errors {
+            if let GameError::Parse {
+                message: Some(ref message),
+                ..
+            } = *e
+            {
+                if !error_messages.contains(message) {
+                    error_messages.push(message.to_owned());
                 }
+            }
+        }
+        let mut suggestions = errors
+            .iter()
+            .flat_map(|e| match *e {
+                GameError::Parse {
+                    ref suggestions, ..
+                } => suggestions.clone(),
+                _ => vec![],
             })
             .collect::<Vec<String>>();
+        suggestions.sort();
+        suggestions.dedup();
         Err(GameError::Parse {
             message: if error_messages.is_empty() {
                 None
             } else {
-                Some(comma_list_or(error_messages))
+                Some(comma_list_or(&error_messages))
             },
             expected: errors
                 .iter()
@@ -509,6 +959,7 @@ impl<T, TP: Parser<T> + ?Sized> Parser<T> for OneOf<T, TP> {
                 })
                 .collect(),
             offset: error_consumed,
+            suggestions,
         })
     }
 
@@ -547,6 +998,8 @@ where
 {
     pub values: Vec<T>,
     pub exact: bool,
+    /// Other spellings for each value, `aliases[i]` being for `values[i]`.
+    pub aliases: Vec<Vec<String>>,
 }
 
 impl<T> Enum<T>
@@ -557,6 +1010,7 @@ where
         Self {
             values: values,
             exact: true,
+            aliases: vec![],
         }
     }
 
@@ -564,25 +1018,55 @@ where
         Self {
             values: values,
             exact: false,
+            aliases: vec![],
         }
     }
+
+    /// Create an enum where each value can also be spelled any of the ways given with it.
+    pub fn aliased<S: Into<String>>(values: Vec<(T, Vec<S>)>, exact: bool) -> Self {
+        let (values, aliases): (Vec<T>, Vec<Vec<String>>) = values
+            .into_iter()
+            .map(|(v, aliases)| (v, aliases.into_iter().map(|a| a.into()).collect()))
+            .unzip();
+        Self {
+            values,
+            exact,
+            aliases,
+        }
+    }
+
+    /// Every spelling of every value, paired with the index of the value. Duplicate spellings
+    /// are skipped so they don't make a match ambiguous, the first one is kept.
+    fn spellings(&self) -> Vec<(usize, String)> {
+        // Track which spellings have been seen to avoid duplicates.
+        let mut searched: HashSet<String> = HashSet::new();
+        self.values
+            .iter()
+            .enumerate()
+            .flat_map(|(i, v)| {
+                Some(v.to_string())
+                    .into_iter()
+                    .chain(self.aliases.get(i).into_iter().flat_map(|a| a.iter().cloned()))
+                    .map(move |s| (i, s))
+            })
+            .filter(|(_, s)| searched.insert(s.to_lowercase()))
+            .collect()
+    }
 }
 
-fn shared_prefix(s1: &str, s2: &str) -> usize {
-    let mut s1i = s1.chars();
-    let mut s2i = s2.chars();
+/// The number of characters `input` shares with the start of `value` ignoring case, and the
+/// length in bytes of that part of `input`.
+fn shared_prefix(input: &str, value: &str) -> (usize, usize) {
     let mut len = 0usize;
-    loop {
-        match (s1i.next(), s2i.next()) {
-            (Some(s1c), Some(s2c)) => {
-                if s1c != s2c {
-                    return len;
-                }
-                len += 1;
-            }
-            _ => return len,
+    let mut bytes = 0usize;
+    for (ic, vc) in input.chars().zip(value.chars()) {
+        if !ic.to_lowercase().eq(vc.to_lowercase()) {
+            break;
         }
+        len += 1;
+        bytes += ic.len_utf8();
     }
+    (len, bytes)
 }
 
 impl<T> Parser<T> for Enum<T>
@@ -590,60 +1074,67 @@ where
     T: ToString + Clone,
 {
     fn parse<'a>(&self, input: &'a str, names: &[String]) -> Result<Output<'a, T>, GameError> {
-        let input_lower = input.to_lowercase();
-        let mut matched: Vec<&T> = vec![];
-        let mut match_len: usize = 0;
-        // Exact matches are prioritised, a shorter full match will happen over a longer partial
-        // match.
-        let mut full_match = false;
-        // Track which values have been searched to avoid duplicates.
-        let mut searched: HashSet<String> = HashSet::new();
-        for v in &self.values {
-            let v_str = v.clone().to_string().to_lowercase();
-            if searched.contains(&v_str) {
-                // This is a duplicate, skip it.
-                continue;
-            }
-            searched.insert(v_str.clone());
-            let v_len = v_str.len();
-            let matching = shared_prefix(&input_lower, &v_str);
-            if self.exact && matching < v_len {
-                // The input isn't long enough and we require exact match, skip it.
-                continue;
-            }
-            if matching > 0 && matching >= match_len && (!full_match || matching == v_len) {
-                if matching == v_len {
-                    full_match = true
-                }
-                if matching > match_len {
-                    matched = vec![v];
-                    match_len = matching;
+        // Full matches are prioritised, so a shorter full match will happen over a longer partial
+        // match. Otherwise the longest match wins.
+        let candidates = self
+            .spellings()
+            .into_iter()
+            .filter_map(|(i, spelling)| {
+                let (matching, bytes) = shared_prefix(input, &spelling);
+                let full = matching == spelling.chars().count();
+                if matching == 0 || (self.exact && !full) {
+                    None
                 } else {
-                    matched.push(v);
+                    Some((i, (full, matching), bytes))
                 }
+            })
+            .collect::<Vec<(usize, (bool, usize), usize)>>();
+        let best = candidates.iter().map(|&(_, key, _)| key).max();
+        let mut matched: Vec<usize> = vec![];
+        for &(i, key, _) in &candidates {
+            if Some(key) == best && !matched.contains(&i) {
+                matched.push(i);
             }
         }
         match matched.len() {
-            1 => Ok(Output {
-                value: matched[0].to_owned(),
-                consumed: &input[..match_len],
-                remaining: &input[match_len..],
-            }),
-            0 => Err(GameError::Parse {
-                message: None,
-                expected: self.expected(names),
-                offset: 0,
-            }),
+            1 => {
+                // Consume as much as any spelling of the value matched.
+                let match_bytes = candidates
+                    .iter()
+                    .filter(|&&(i, _, _)| i == matched[0])
+                    .map(|&(_, _, bytes)| bytes)
+                    .max()
+                    .unwrap_or(0);
+                Ok(Output {
+                    value: self.values[matched[0]].to_owned(),
+                    consumed: &input[..match_bytes],
+                    remaining: &input[match_bytes..],
+                })
+            }
+            0 => {
+                let spellings = self.spellings();
+                Err(unknown_error(
+                    input,
+                    spellings.iter().map(|(_, s)| s.as_ref()),
+                    self.expected(names),
+                ).unwrap_or_else(|| GameError::Parse {
+                    message: None,
+                    expected: self.expected(names),
+                    offset: 0,
+                    suggestions: vec![],
+                }))
+            }
             _ => Err(GameError::Parse {
                 message: Some(format!(
                     "matched {}, more input is required to uniquely match one",
                     comma_list_and(&matched
                         .iter()
-                        .map(|m| m.to_string())
+                        .map(|&m| self.values[m].to_string())
                         .collect::<Vec<String>>()),
                 )),
                 expected: self.expected(names),
                 offset: 0,
+                suggestions: vec![],
             }),
         }
     }
@@ -654,6 +1145,7 @@ where
             .map(|v| v.to_string())
             .collect::<Vec<String>>();
         values.sort();
+        values.dedup();
         values
     }
 
@@ -661,6 +1153,7 @@ where
         CommandSpec::Enum {
             values: self.values.iter().cloned().map(|v| v.to_string()).collect(),
             exact: self.exact,
+            aliases: self.aliases.clone(),
         }
     }
 }
@@ -694,7 +1187,9 @@ impl<T, TP: Parser<T>> Doc<T, TP> {
 
 impl<T, TP: Parser<T>> Parser<T> for Doc<T, TP> {
     fn parse<'a>(&self, input: &'a str, names: &[String]) -> Result<Output<'a, T>, GameError> {
-        self.parser.parse(input, names)
+        self.parser
+            .parse(input, names)
+            .map_err(|e| name_unknown(e, &self.name))
     }
 
     fn expected(&self, names: &[String]) -> Vec<String> {
@@ -741,7 +1236,9 @@ impl Player {
 
 impl Parser<usize> for Player {
     fn parse<'a>(&self, input: &'a str, names: &[String]) -> Result<Output<'a, usize>, GameError> {
-        Map::new(Enum::partial(self.player_nums(names)), |pn| pn.num).parse(input, names)
+        Map::new(Enum::partial(self.player_nums(names)), |pn| pn.num)
+            .parse(input, names)
+            .map_err(|e| name_unknown(e, "player"))
     }
 
     fn expected(&self, names: &[String]) -> Vec<String> {
@@ -840,6 +1337,184 @@ mod tests {
             .expect_err("expected '101' to produce an error when maximum is set");
     }
 
+    #[test]
+    fn float_parser_works() {
+        let parser = Float::bounded(-1.5, 10.0).precision(2);
+        let parse = |input: &str| {
+            let output = parser
+                .parse(input, &[])
+                .unwrap_or_else(|e| panic!("expected '{}' to parse: {}", input, e));
+            (output.value, output.remaining.to_string())
+        };
+        assert_eq!((2.5, " bacon".to_string()), parse("2.5 bacon"));
+        assert_eq!((-1.25, "".to_string()), parse("-1.25"));
+        assert_eq!((3.0, ". done".to_string()), parse("3. done"));
+        assert_eq!((0.5, "".to_string()), parse(".5"));
+        assert_eq!((7.0, "".to_string()), parse("7"));
+        let message = |input: &str| {
+            parser
+                .parse(input, &[])
+                .expect_err("expected input to produce an error")
+                .to_string()
+        };
+        assert_eq!(
+            "10.5 is too high, expected number with up to 2 decimal places between -1.5 and 10",
+            message("10.5")
+        );
+        assert_eq!(
+            "-2 is too low, expected number with up to 2 decimal places between -1.5 and 10",
+            message("-2")
+        );
+        assert!(message("1.125").starts_with("1.125 has too many decimal places"));
+        parser
+            .parse("-.", &[])
+            .expect_err("expected '-.' to produce an error");
+    }
+
+    #[test]
+    fn text_parser_works() {
+        let parser = Text::bounded(2, 5);
+        let parse = |input: &str| {
+            let output = parser
+                .parse(input, &[])
+                .unwrap_or_else(|e| panic!("expected '{}' to parse: {}", input, e));
+            (output.value, output.consumed.to_string(), output.remaining.to_string())
+        };
+        assert_eq!(
+            ("egg".to_string(), "egg".to_string(), " bacon".to_string()),
+            parse("egg bacon")
+        );
+        assert_eq!(
+            ("ab".to_string(), "ab".to_string(), ", cd".to_string()),
+            parse("ab, cd")
+        );
+        assert_eq!(
+            ("a \"b".to_string(), r#""a \"b""#.to_string(), " c".to_string()),
+            parse(r#""a \"b" c"#)
+        );
+        assert_eq!(
+            ("it's".to_string(), r#"'it\'s'"#.to_string(), "".to_string()),
+            parse(r#"'it\'s'"#)
+        );
+        let message = |parser: &Text, input: &str| {
+            parser
+                .parse(input, &[])
+                .expect_err("expected input to produce an error")
+                .to_string()
+        };
+        assert_eq!(
+            "'a' is too short, expected text of 2 to 5 characters",
+            message(&parser, "a")
+        );
+        assert_eq!(
+            "'bacon!' is too long, expected text of 2 to 5 characters",
+            message(&parser, "bacon!")
+        );
+        assert!(message(&parser, r#""egg"#).starts_with("missing closing \""));
+        assert!(message(&parser, " egg").starts_with("expected text"));
+        assert_eq!(
+            "'ab1' can only contain letters, expected word",
+            message(&Text::any().chars(CharClass::Alphabetic), "ab1")
+        );
+    }
+
+    #[test]
+    fn and_then_parser_works() {
+        let parser = AndThen::new(
+            Doc::name("card", Enum::partial(vec!["sword", "wand", "shield"])),
+            |card: &&str| -> Box<dyn Parser<String>> {
+                match *card {
+                    "sword" => Box::new(Map::new(AfterSpace::new(Player {}), |p| p.to_string())),
+                    "wand" => Box::new(Map::new(AfterSpace::new(Int::bounded(1, 3)), |i| {
+                        i.to_string()
+                    })),
+                    _ => Box::new(Map::new(Space {}, |_| String::new())),
+                }
+            },
+        );
+        let names = vec!["mick".to_string(), "steve".to_string()];
+        let output = parser
+            .parse("sw st now", &names)
+            .expect("expected 'sw st now' to parse");
+        assert_eq!(("sword", "1".to_string()), output.value);
+        assert_eq!(" now", output.remaining);
+        assert_eq!(
+            ("wand", "2".to_string()),
+            parser.parse("wand 2", &names).expect("expected 'wand 2' to parse").value
+        );
+        match parser.parse("wand 4", &names) {
+            Err(GameError::Parse { offset, .. }) => assert_eq!(5, offset),
+            result => panic!("expected a parse error, got {:?}", result.map(|o| o.value)),
+        }
+        let spec = parser.to_spec();
+        match spec {
+            CommandSpec::OneOf(ref branches) => {
+                assert_eq!(3, branches.len());
+                assert_eq!(
+                    CommandSpec::Chain(vec![
+                        CommandSpec::Doc {
+                            name: "card".to_string(),
+                            desc: None,
+                            spec: Box::new(CommandSpec::Enum {
+                                values: vec![
+                                    "sword".to_string(),
+                                    "wand".to_string(),
+                                    "shield".to_string(),
+                                ],
+                                exact: false,
+                                aliases: vec![],
+                            }),
+                        },
+                        Map::new(AfterSpace::new(Int::bounded(1, 3)), |i| i).to_spec(),
+                    ]),
+                    branches[1]
+                );
+            }
+            ref spec => panic!("expected a one of spec, got {:?}", spec),
+        }
+        // The spec agrees with the parser that a prefix of two cards is ambiguous.
+        parser.parse("s 2", &names).expect_err("expected 's' to be ambiguous");
+        spec.parse("s 2", &names).expect_err("expected 's' to be ambiguous for the spec");
+        assert_eq!(
+            "sh ",
+            spec.parse("sh ", &names).expect("expected 'sh ' to parse").consumed
+        );
+        assert_eq!(
+            CommandSpec::Chain(vec![
+                CommandSpec::Int {
+                    min: Some(1),
+                    max: None,
+                },
+                CommandSpec::Space,
+            ]),
+            AndThen::new(Int::positive(), |_: &i32| Space {}).to_spec()
+        );
+        // Inputs which can't be listed are sampled to find what can follow them.
+        let spec = AndThen::new(Player {}, |p: &usize| -> Box<dyn Parser<String>> {
+            if *p == 0 {
+                Box::new(Map::new(AfterSpace::new(Int::positive()), |i| i.to_string()))
+            } else {
+                Box::new(Map::new(Space {}, |_| String::new()))
+            }
+        }).to_spec();
+        match spec {
+            CommandSpec::Chain(ref specs) => {
+                assert_eq!(CommandSpec::Player, specs[0]);
+                match specs[1] {
+                    CommandSpec::OneOf(ref tails) => {
+                        assert_eq!(2, tails.len());
+                        assert!(tails.contains(&CommandSpec::Space));
+                        assert!(tails.contains(
+                            &Map::new(AfterSpace::new(Int::positive()), |i| i).to_spec()
+                        ));
+                    }
+                    ref tail => panic!("expected a one of spec, got {:?}", tail),
+                }
+            }
+            ref spec => panic!("expected a chain spec, got {:?}", spec),
+        }
+    }
+
     #[test]
     fn map_parser_works() {
         let parser = Map::new(
@@ -1024,6 +1699,147 @@ mod tests {
                 .parse("DoGlog", &[],)
                 .expect("expected 'DoGlog' to parse",)
         );
+        // Duplicate values, such as two identical cards in a hand, aren't ambiguous.
+        let duplicates = Enum::partial(vec!["egg", "egg", "bacon", "Egg"]);
+        assert_eq!(
+            Output {
+                value: "egg",
+                consumed: "egg",
+                remaining: "",
+            },
+            duplicates.parse("egg", &[]).expect("expected 'egg' to parse")
+        );
+        assert_eq!(
+            "egg",
+            duplicates.parse("e", &[]).expect("expected 'e' to parse").value
+        );
+        assert_eq!(vec!["Egg", "bacon", "egg"], duplicates.expected(&[]));
+        let aliased = Enum::aliased(vec![("egg", vec!["egg"]), ("eggs", vec!["egg"])], true);
+        assert_eq!(
+            "egg",
+            aliased.parse("egg", &[]).expect("expected aliased 'egg' to parse").value
+        );
+    }
+
+    fn error_offset<T, P: Parser<T>>(parser: &P, input: &str, names: &[String]) -> usize {
+        match parser.parse(input, names) {
+            Err(GameError::Parse { offset, .. }) => offset,
+            _ => panic!("expected '{}' to produce a parse error", input),
+        }
+    }
+
+    #[test]
+    fn error_offsets_are_absolute() {
+        let names = vec!["mick".to_string(), "steve".to_string()];
+        let parser = Chain3::new(
+            Token::new("give"),
+            AfterSpace::new(Player {}),
+            AfterSpace::new(Many::bounded(Enum::exact(vec!["egg", "bacon"]), 2, 3)),
+        );
+        let spec = parser.to_spec();
+        for &(input, offset) in &[
+            ("take", 0),
+            ("give", 4),
+            ("give bob", 5),
+            ("give mick ham", 10),
+            ("give mick egg", 13),
+            ("give mick egg, ham", 15),
+        ] {
+            assert_eq!(offset, error_offset(&parser, input, &names), "for '{}'", input);
+            assert_eq!(offset, error_offset(&spec, input, &names), "for spec '{}'", input);
+        }
+        let parser = Chain4::new(
+            Token::new("a"),
+            Token::new("b"),
+            Token::new("c"),
+            Int::bounded(1, 3),
+        );
+        assert_eq!(3, error_offset(&parser, "abc4", &names));
+    }
+
+    #[test]
+    fn suggestions_work() {
+        let names = vec!["mick".to_string(), "steve".to_string()];
+        let parser = Chain2::new(
+            Token::new("play"),
+            AfterSpace::new(Doc::name("card", Enum::exact(vec!["shield", "sword"]))),
+        );
+        let message = |input: &str| {
+            parser
+                .parse(input, &names)
+                .expect_err("expected input to produce an error")
+                .to_string()
+        };
+        assert_eq!("unknown card 'sheild', did you mean 'shield'?", message("play sheild"));
+        assert_eq!("unknown 'plya', did you mean 'play'?", message("plya shield"));
+        assert_eq!("expected shield or sword", message("play axe"));
+        assert_eq!(
+            "unknown 'red dragn', did you mean 'red dragon'?",
+            Enum::exact(vec!["red dragon", "bat"])
+                .parse("red dragn", &names)
+                .expect_err("expected 'red dragn' to produce an error")
+                .to_string()
+        );
+        assert_eq!(
+            "unknown player 'tseve', did you mean 'steve'?",
+            Player {}
+                .parse("tseve", &names)
+                .expect_err("expected 'tseve' to produce an error")
+                .to_string()
+        );
+        let parsers: Vec<Box<dyn Parser<String>>> = vec![
+            Box::new(Token::new("pass")),
+            Box::new(Token::new("pack")),
+            Box::new(Token::new("bid")),
+        ];
+        assert_eq!(
+            "unknown 'pacs', did you mean 'pack' or 'pass'?",
+            OneOf::new(parsers)
+                .parse("pacs", &names)
+                .expect_err("expected 'pacs' to produce an error")
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn enum_aliases_work() {
+        let parser = Enum::aliased(
+            vec![
+                ("hearts", vec!["h", "♥"]),
+                ("spades", vec!["s", "♠"]),
+                ("red dragon", vec![]),
+                ("red", vec![]),
+            ],
+            false,
+        );
+        let parse = |input: &str| {
+            let output = parser
+                .parse(input, &[])
+                .unwrap_or_else(|e| panic!("expected '{}' to parse: {}", input, e));
+            (output.value, output.consumed.to_string(), output.remaining.to_string())
+        };
+        let owned = |value, consumed: &str, remaining: &str| {
+            (value, consumed.to_string(), remaining.to_string())
+        };
+        assert_eq!(owned("hearts", "♥", " 3"), parse("♥ 3"));
+        assert_eq!(owned("spades", "S", ""), parse("S"));
+        assert_eq!(owned("spades", "sp", ""), parse("sp"));
+        assert_eq!(owned("red dragon", "Red Dragon", " now"), parse("Red Dragon now"));
+        // A full match beats a longer partial one whatever order the values are in.
+        assert_eq!(owned("red", "red", " d"), parse("red d"));
+        parser
+            .parse("re", &[])
+            .expect_err("expected 're' to be ambiguous");
+        let parser = Enum::partial(vec!["farty", "fart"]);
+        assert_eq!("fart", parser.parse("fart", &[]).expect("expected 'fart' to parse").value);
+        assert_eq!(
+            CommandSpec::Enum {
+                values: vec!["fart".to_string(), "bacon".to_string()],
+                exact: true,
+                aliases: vec![vec!["f".to_string()], vec![]],
+            },
+            Enum::aliased(vec![("fart", vec!["f"]), ("bacon", vec![])], true).to_spec()
+        );
     }
 
     #[test]
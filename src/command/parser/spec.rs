@@ -0,0 +1,428 @@
+use serde_derive::{Deserialize, Serialize};
+
+use std::fmt;
+
+use crate::command::parser::{
+    offset_error, Enum, Float, Int, Many, OneOf, Opt, Output, Parser, Player, Space, Text, Token,
+};
+use crate::command::Spec as CommandSpec;
+use crate::errors::GameError;
+
+/// The generic parse tree produced when parsing with a `Spec`. `OneOf` and `Doc` are transparent
+/// and produce the value of their child.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SpecValue {
+    Int(i32),
+    Float(f64),
+    Text(String),
+    /// The column and row for grids, or q and r for axial coordinates.
+    Coord(i32, i32),
+    Token(String),
+    Enum { index: usize, value: String },
+    Player(usize),
+    Chain(Vec<SpecValue>),
+    Many(Vec<SpecValue>),
+    Opt(Option<Box<SpecValue>>),
+    Space(String),
+}
+
+#[derive(Clone)]
+struct EnumValue {
+    index: usize,
+    value: String,
+}
+
+impl fmt::Display for EnumValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+fn enum_parser(values: &[String], exact: bool, aliases: &[Vec<String>]) -> Enum<EnumValue> {
+    Enum {
+        values: values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| EnumValue {
+                index,
+                value: value.to_owned(),
+            })
+            .collect(),
+        exact,
+        aliases: aliases.to_vec(),
+    }
+}
+
+fn many_parser<'s>(
+    spec: &'s CommandSpec,
+    min: Option<usize>,
+    max: Option<usize>,
+    delim: &str,
+) -> Many<SpecValue, &'s CommandSpec> {
+    let mut parser = Many::any(spec);
+    parser.min = min;
+    parser.max = max;
+    parser.delim = delim.to_owned();
+    parser
+}
+
+fn map_output<T, F>(output: Output<T>, f: F) -> Output<SpecValue>
+where
+    F: Fn(T) -> SpecValue,
+{
+    Output {
+        value: f(output.value),
+        consumed: output.consumed,
+        remaining: output.remaining,
+    }
+}
+
+/// A `Spec` can be used as a parser directly, accepting exactly the same input as the parser it
+/// was created from. This allows input to be validated without access to the original parser.
+impl Parser<SpecValue> for CommandSpec {
+    fn parse<'a>(
+        &self,
+        input: &'a str,
+        names: &[String],
+    ) -> Result<Output<'a, SpecValue>, GameError> {
+        Ok(match *self {
+            CommandSpec::Int { min, max } => {
+                map_output(Int { min, max }.parse(input, names)?, SpecValue::Int)
+            }
+            CommandSpec::Float {
+                min,
+                max,
+                precision,
+            } => map_output(
+                Float {
+                    min,
+                    max,
+                    precision,
+                }.parse(input, names)?,
+                SpecValue::Float,
+            ),
+            CommandSpec::Text { min, max, chars } => map_output(
+                Text { min, max, chars }.parse(input, names)?,
+                SpecValue::Text,
+            ),
+            CommandSpec::Coord(ref form) => {
+                map_output(form.parse(input, names)?, |(x, y)| SpecValue::Coord(x, y))
+            }
+            CommandSpec::Token(ref token) => {
+                map_output(Token::new(token.to_owned()).parse(input, names)?, SpecValue::Token)
+            }
+            CommandSpec::Enum {
+                ref values,
+                exact,
+                ref aliases,
+            } => {
+                map_output(enum_parser(values, exact, aliases).parse(input, names)?, |v| {
+                    SpecValue::Enum {
+                        index: v.index,
+                        value: v.value,
+                    }
+                })
+            }
+            CommandSpec::OneOf(ref specs) => {
+                OneOf::new(specs.iter().map(Box::new).collect()).parse(input, names)?
+            }
+            CommandSpec::Chain(ref specs) => {
+                let mut values: Vec<SpecValue> = vec![];
+                let mut consumed = 0;
+                for spec in specs {
+                    let output = spec
+                        .parse(&input[consumed..], names)
+                        .map_err(|e| offset_error(e, consumed))?;
+                    consumed += output.consumed.len();
+                    values.push(output.value);
+                }
+                Output {
+                    value: SpecValue::Chain(values),
+                    consumed: &input[..consumed],
+                    remaining: &input[consumed..],
+                }
+            }
+            CommandSpec::Many {
+                ref spec,
+                min,
+                max,
+                ref delim,
+            } => map_output(
+                many_parser(spec, min, max, delim).parse(input, names)?,
+                SpecValue::Many,
+            ),
+            CommandSpec::Opt(ref spec) => map_output(
+                Opt::new(spec.as_ref()).parse(input, names)?,
+                |v| SpecValue::Opt(v.map(Box::new)),
+            ),
+            CommandSpec::Doc { ref spec, .. } => spec.parse(input, names)?,
+            CommandSpec::Player => map_output(Player {}.parse(input, names)?, SpecValue::Player),
+            CommandSpec::Space => map_output(Space {}.parse(input, names)?, SpecValue::Space),
+        })
+    }
+
+    fn expected(&self, names: &[String]) -> Vec<String> {
+        match *self {
+            CommandSpec::Int { min, max } => Int { min, max }.expected(names),
+            CommandSpec::Float {
+                min,
+                max,
+                precision,
+            } => Float {
+                min,
+                max,
+                precision,
+            }.expected(names),
+            CommandSpec::Text { min, max, chars } => Text { min, max, chars }.expected(names),
+            CommandSpec::Coord(ref form) => form.expected(names),
+            CommandSpec::Token(ref token) => Token::new(token.to_owned()).expected(names),
+            CommandSpec::Enum {
+                ref values,
+                exact,
+                ref aliases,
+            } => enum_parser(values, exact, aliases).expected(names),
+            CommandSpec::OneOf(ref specs) => specs.iter().flat_map(|s| s.expected(names)).collect(),
+            CommandSpec::Chain(ref specs) => specs
+                .first()
+                .map(|s| s.expected(names))
+                .unwrap_or_default(),
+            CommandSpec::Many {
+                ref spec,
+                min,
+                max,
+                ref delim,
+            } => many_parser(spec, min, max, delim).expected(names),
+            CommandSpec::Opt(ref spec) => Opt::new(spec.as_ref()).expected(names),
+            CommandSpec::Doc { ref spec, .. } => spec.expected(names),
+            CommandSpec::Player => Player {}.expected(names),
+            CommandSpec::Space => Space {}.expected(names),
+        }
+    }
+
+    fn to_spec(&self) -> CommandSpec {
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::parser::*;
+
+    fn assert_same<T, P: Parser<T> + ?Sized>(parser: &P, inputs: &[&str], names: &[String]) {
+        let spec = parser.to_spec();
+        for input in inputs {
+            match (parser.parse(input, names), spec.parse(input, names)) {
+                (Ok(typed), Ok(generic)) => {
+                    assert_eq!(typed.consumed, generic.consumed, "for input '{}'", input)
+                }
+                (Err(_), Err(_)) => {}
+                (typed, generic) => panic!(
+                    "for input '{}' typed parser gave {:?} but spec gave {:?}",
+                    input,
+                    typed.map(|o| o.consumed),
+                    generic.map(|o| o.consumed)
+                ),
+            }
+        }
+    }
+
+    /// Check the value the spec produces for each input, and that the typed parser agrees on
+    /// what was consumed.
+    fn assert_values<T, P: Parser<T> + ?Sized>(
+        parser: &P,
+        cases: &[(&str, SpecValue)],
+        names: &[String],
+    ) {
+        let spec = parser.to_spec();
+        for &(input, ref value) in cases {
+            let generic = spec
+                .parse(input, names)
+                .unwrap_or_else(|e| panic!("expected spec to parse '{}': {}", input, e));
+            assert_eq!(value, &generic.value, "for input '{}'", input);
+            let typed = parser
+                .parse(input, names)
+                .unwrap_or_else(|e| panic!("expected parser to parse '{}': {}", input, e));
+            assert_eq!(typed.consumed, generic.consumed, "for input '{}'", input);
+        }
+    }
+
+    fn after_space(value: SpecValue) -> SpecValue {
+        SpecValue::Chain(vec![SpecValue::Space(" ".to_string()), value])
+    }
+
+    fn enum_value(index: usize, value: &str) -> SpecValue {
+        SpecValue::Enum {
+            index,
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn spec_parser_matches_typed_parsers() {
+        let names = vec!["mick".to_string(), "steve".to_string()];
+        let inputs = [
+            "",
+            "bid 3",
+            "bid 30",
+            "bid",
+            "give st",
+            "give bob",
+            "play cheese, bacon 5",
+            "play ch,ba,eg",
+            "play cheese,  bacon , egg, bacon",
+            "play",
+            "play 5",
+            "PLAY EGG",
+        ];
+        let parsers: Vec<Box<dyn Parser<String>>> = vec![
+            Box::new(Map::new(
+                Chain2::new(Token::new("bid"), AfterSpace::new(Int::bounded(-5, 5))),
+                |(_, i)| i.to_string(),
+            )),
+            Box::new(Map::new(
+                Chain2::new(Token::new("give"), AfterSpace::new(Player {})),
+                |(_, p)| p.to_string(),
+            )),
+            Box::new(Map::new(
+                Chain3::new(
+                    Token::new("play"),
+                    AfterSpace::new(Many::bounded(
+                        Enum::exact(vec!["cheese", "bacon", "egg"]),
+                        1,
+                        3,
+                    )),
+                    Opt::new(AfterSpace::new(Int::positive())),
+                ),
+                |(_, cards, n)| format!("{:?} {:?}", cards, n),
+            )),
+            Box::new(Map::new(
+                Chain2::new(
+                    Token::new("play"),
+                    AfterSpace::new(Many::any(Enum::partial(vec!["cheese", "bacon", "egg"]))),
+                ),
+                |(_, cards)| format!("{:?}", cards),
+            )),
+        ];
+        for parser in &parsers {
+            assert_same(parser.as_ref(), &inputs, &names);
+        }
+        let token = |t: &str| SpecValue::Token(t.to_string());
+        assert_values(
+            parsers[0].as_ref(),
+            &[
+                ("bid 3", SpecValue::Chain(vec![token("bid"), after_space(SpecValue::Int(3))])),
+                ("bid -5", SpecValue::Chain(vec![token("bid"), after_space(SpecValue::Int(-5))])),
+            ],
+            &names,
+        );
+        assert_values(
+            parsers[1].as_ref(),
+            &[(
+                "give st",
+                SpecValue::Chain(vec![token("give"), after_space(SpecValue::Player(1))]),
+            )],
+            &names,
+        );
+        assert_values(
+            parsers[2].as_ref(),
+            &[
+                (
+                    "play cheese, bacon 5",
+                    SpecValue::Chain(vec![
+                        token("play"),
+                        after_space(SpecValue::Many(vec![
+                            enum_value(0, "cheese"),
+                            enum_value(1, "bacon"),
+                        ])),
+                        SpecValue::Opt(Some(Box::new(after_space(SpecValue::Int(5))))),
+                    ]),
+                ),
+                (
+                    "PLAY EGG",
+                    SpecValue::Chain(vec![
+                        token("play"),
+                        after_space(SpecValue::Many(vec![enum_value(2, "egg")])),
+                        SpecValue::Opt(None),
+                    ]),
+                ),
+            ],
+            &names,
+        );
+        assert_values(
+            parsers[3].as_ref(),
+            &[(
+                "play ch,ba,eg",
+                SpecValue::Chain(vec![
+                    token("play"),
+                    after_space(SpecValue::Many(vec![
+                        enum_value(0, "cheese"),
+                        enum_value(1, "bacon"),
+                        enum_value(2, "egg"),
+                    ])),
+                ]),
+            )],
+            &names,
+        );
+        assert_same(&OneOf::new(parsers), &inputs, &names);
+    }
+
+    #[test]
+    fn spec_parser_produces_scalar_values() {
+        assert_values(&Float::any(), &[("1.5", SpecValue::Float(1.5))], &[]);
+        assert_values(
+            &Text::any(),
+            &[
+                ("egg", SpecValue::Text("egg".to_string())),
+                (r#""bacon and egg""#, SpecValue::Text("bacon and egg".to_string())),
+            ],
+            &[],
+        );
+        assert_values(&Coord::chess(8, 8), &[("c3", SpecValue::Coord(2, 2))], &[]);
+        assert_values(&HexCoord::new(2), &[("-1,2", SpecValue::Coord(-1, 2))], &[]);
+    }
+
+    #[test]
+    fn spec_parser_produces_values() {
+        let parser = Chain2::new(
+            Token::new("give"),
+            AfterSpace::new(Many::some(Chain3::new(
+                Doc::name("player", Player {}),
+                Space {},
+                Opt::new(Enum::partial(vec!["cheese", "bacon"])),
+            ))),
+        );
+        let names = vec!["mick".to_string(), "steve".to_string()];
+        assert_eq!(
+            Output {
+                value: SpecValue::Chain(vec![
+                    SpecValue::Token("give".to_string()),
+                    SpecValue::Chain(vec![
+                        SpecValue::Space(" ".to_string()),
+                        SpecValue::Many(vec![
+                            SpecValue::Chain(vec![
+                                SpecValue::Player(1),
+                                SpecValue::Space(" ".to_string()),
+                                SpecValue::Opt(Some(Box::new(SpecValue::Enum {
+                                    index: 1,
+                                    value: "bacon".to_string(),
+                                }))),
+                            ]),
+                            SpecValue::Chain(vec![
+                                SpecValue::Player(0),
+                                SpecValue::Space(" ".to_string()),
+                                SpecValue::Opt(None),
+                            ]),
+                        ]),
+                    ]),
+                ]),
+                consumed: "give st b, m ",
+                remaining: "",
+            },
+            parser
+                .to_spec()
+                .parse("give st b, m ", &names)
+                .expect("expected 'give st b, m ' to parse")
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use unicase::UniCase;
+
+use crate::errors::GameError;
+
+fn chars_eq(a: char, b: char) -> bool {
+    a == b || UniCase(a.to_string()) == UniCase(b.to_string())
+}
+
+/// The Damerau-Levenshtein distance between two strings, ignoring case. Only adjacent
+/// transpositions are counted, so it is the optimal string alignment distance.
+pub fn distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<char>>();
+    let b = b.chars().collect::<Vec<char>>();
+    let mut rows: Vec<Vec<usize>> = (0..=a.len())
+        .map(|i| (0..=b.len()).map(|j| if i == 0 { j } else { i }).collect())
+        .collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if chars_eq(a[i - 1], b[j - 1]) { 0 } else { 1 };
+            let mut d = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && chars_eq(a[i - 1], b[j - 2]) && chars_eq(a[i - 2], b[j - 1]) {
+                d = d.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = d;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// The first `words` words of the input, which is what a user meant to be a value of that many
+/// words. Values end at a comma.
+pub fn leading_words(input: &str, words: usize) -> &str {
+    let mut seen = 0;
+    let mut in_word = false;
+    for (i, c) in input.char_indices() {
+        if c == ',' || (c.is_whitespace() && seen == 0 && !in_word) {
+            return &input[..i];
+        }
+        if !c.is_whitespace() {
+            in_word = true;
+        } else if in_word {
+            in_word = false;
+            seen += 1;
+            if seen >= words {
+                return &input[..i];
+            }
+        }
+    }
+    input
+}
+
+fn word_count(s: &str) -> usize {
+    s.split_whitespace().count().max(1)
+}
+
+/// The candidates closest to the start of `input`, allowing one edit for every three characters
+/// so short candidates are never suggested. Each candidate is compared with as many words of the
+/// input as it has.
+pub fn suggest<'a, I>(input: &str, candidates: I) -> Vec<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut best: Vec<String> = vec![];
+    let mut best_distance = usize::MAX;
+    for candidate in candidates {
+        let word = leading_words(input, word_count(candidate));
+        if word.is_empty() {
+            continue;
+        }
+        let d = distance(word, candidate);
+        if d == 0 || d > candidate.chars().count() / 3 || d > best_distance {
+            continue;
+        }
+        if d < best_distance {
+            best = vec![];
+            best_distance = d;
+        }
+        if !best.iter().any(|b| b == candidate) {
+            best.push(candidate.to_owned());
+        }
+    }
+    best.sort();
+    best
+}
+
+/// A parse error for an unknown word, with the suggestions which are close to it. `None` if
+/// nothing is close so the caller can fall back to its normal error.
+pub fn unknown_error<'a, I>(
+    input: &str,
+    candidates: I,
+    expected: Vec<String>,
+) -> Option<GameError>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let suggestions = suggest(input, candidates);
+    let words = suggestions.iter().map(|s| word_count(s)).max()?;
+    let word = leading_words(input, words);
+    Some(GameError::Parse {
+        message: Some(format!("unknown '{}'", word)),
+        expected,
+        offset: 0,
+        suggestions,
+    })
+}
+
+pub fn has_suggestions(error: &GameError) -> bool {
+    match *error {
+        GameError::Parse {
+            ref suggestions, ..
+        } => !suggestions.is_empty(),
+        _ => false,
+    }
+}
+
+/// Include what was being parsed in the message of an unknown word error from the start of the
+/// input, so it reads as "unknown card 'sheild'".
+pub fn name_unknown(error: GameError, name: &str) -> GameError {
+    match error {
+        GameError::Parse {
+            message: Some(ref message),
+            ref expected,
+            offset: 0,
+            ref suggestions,
+        } if !suggestions.is_empty() && message.starts_with("unknown '") => GameError::Parse {
+            message: Some(format!("unknown {} {}", name, &message["unknown ".len()..])),
+            expected: expected.to_owned(),
+            offset: 0,
+            suggestions: suggestions.to_owned(),
+        },
+        e => e,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_works() {
+        assert_eq!(0, distance("Shield", "sHIELD"));
+        assert_eq!(1, distance("sheild", "shield"));
+        assert_eq!(1, distance("shied", "shield"));
+        assert_eq!(3, distance("kitten", "sitting"));
+        assert_eq!(4, distance("", "fart"));
+    }
+
+    #[test]
+    fn suggest_works() {
+        let candidates = ["shield", "sword", "shovel", "bow"];
+        assert_eq!(vec!["shield"], suggest("sheild", candidates.iter().cloned()));
+        assert_eq!(vec!["bag", "cat"], suggest("bat", vec!["cat", "bag", "bat man"]));
+        assert_eq!(Vec::<String>::new(), suggest("axe", candidates.iter().cloned()));
+        assert_eq!(Vec::<String>::new(), suggest("bow", candidates.iter().cloned()));
+        assert_eq!(Vec::<String>::new(), suggest("x", vec![","]));
+        assert_eq!(
+            vec!["red dragon"],
+            suggest("red dragn attacks", vec!["red dragon", "red", "blue dragon"])
+        );
+    }
+
+    #[test]
+    fn leading_words_works() {
+        assert_eq!("red", leading_words("red dragon", 1));
+        assert_eq!("red dragon", leading_words("red dragon attacks", 2));
+        assert_eq!("red dragon", leading_words("red dragon, bat", 3));
+        assert_eq!("", leading_words(" red", 1));
+    }
+
+    #[test]
+    fn unknown_error_works() {
+        match unknown_error("red dragn attacks", vec!["red dragon", "bat"], vec![]) {
+            Some(GameError::Parse {
+                message: Some(message),
+                suggestions,
+                ..
+            }) => {
+                assert_eq!("unknown 'red dragn'", message);
+                assert_eq!(vec!["red dragon"], suggestions);
+            }
+            e => panic!("expected an unknown error, got {:?}", e),
+        }
+    }
+}
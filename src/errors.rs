@@ -1,10 +1,14 @@
 use failure::Fail;
+use serde_derive::{Serialize, Deserialize};
+
+use brdgme_color::RED;
+use brdgme_markup::Node;
 
 use std::fmt;
 
 use crate::command::parser::comma_list_or;
 
-#[derive(Debug, Fail)]
+#[derive(Debug, Fail, Serialize, Deserialize)]
 pub enum GameError {
     PlayerCount {
         min: usize,
@@ -23,6 +27,9 @@ pub enum GameError {
         message: Option<String>,
         expected: Vec<String>,
         offset: usize,
+        /// Close matches for unknown input.
+        #[serde(default)]
+        suggestions: Vec<String>,
     },
 }
 
@@ -38,6 +45,29 @@ impl GameError {
             message: message.into(),
         }
     }
+
+    /// Render the error for the input which caused it. Parse errors show the input with a caret
+    /// under where parsing failed, other errors are just the message.
+    pub fn diagnostic(&self, input: &str) -> Vec<Node> {
+        let offset = match *self {
+            GameError::Parse { offset, .. } => offset,
+            _ => return vec![Node::text(self.to_string())],
+        };
+        let mut offset = offset.min(input.len());
+        while !input.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        // Tabs are kept so the caret lines up however wide they are displayed.
+        let indent = input[..offset]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        vec![
+            Node::text(format!("{}\n{}", input, indent)),
+            Node::Fg(RED.into(), vec![Node::Bold(vec![Node::text("^")])]),
+            Node::text(format!("\n{}", self)),
+        ]
+    }
 }
 
 impl fmt::Display for GameError {
@@ -53,6 +83,21 @@ impl fmt::Display for GameError {
             GameError::NotYourTurn => write!(f, "not your turn"),
             GameError::Finished => write!(f, "game is already finished"),
             GameError::Internal { ref message } => write!(f, "internal error: {}", message),
+            GameError::Parse {
+                ref message,
+                ref suggestions,
+                ..
+            } if !suggestions.is_empty() => write!(
+                f,
+                "{}, did you mean {}?",
+                message.as_ref().map(|m| m.as_ref()).unwrap_or("unknown input"),
+                comma_list_or(
+                    &suggestions
+                        .iter()
+                        .map(|s| format!("'{}'", s))
+                        .collect::<Vec<String>>()
+                )
+            ),
             GameError::Parse {
                 ref message,
                 ref expected,
@@ -77,3 +122,29 @@ fn player_range_output(min: usize, max: usize) -> String {
         format!("{} to {}", min, max)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::parser::{AfterSpace, Chain2, Int, Parser, Token};
+
+    #[test]
+    fn diagnostic_works() {
+        let input = "add 4";
+        let error = Chain2::new(Token::new("add"), AfterSpace::new(Int::bounded(1, 3)))
+            .parse(input, &[])
+            .expect_err("expected 'add 4' to produce an error");
+        assert_eq!(
+            vec![
+                Node::text("add 4\n    "),
+                Node::Fg(RED.into(), vec![Node::Bold(vec![Node::text("^")])]),
+                Node::text("\n4 is too high, expected number between 1 and 3"),
+            ],
+            error.diagnostic(input)
+        );
+        assert_eq!(
+            vec![Node::text("not your turn")],
+            GameError::NotYourTurn.diagnostic(input)
+        );
+    }
+}
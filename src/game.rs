@@ -1,16 +1,20 @@
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_derive::{Serialize, Deserialize};
+use serde_json;
+use rand::{self, Rng};
 
 use brdgme_markup::Node;
 
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::command;
 use crate::errors::GameError;
 use crate::game_log::Log;
 
+pub const MAX_ENUMERATED_MOVES: usize = 10_000;
+
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Stat {
     Int(i32),
@@ -47,11 +51,31 @@ pub struct CommandResponse {
     pub remaining_input: String,
 }
 
+/// The message of the error returned by the default `Gamer::validate`.
+pub(crate) const VALIDATE_UNSUPPORTED: &str = "this game doesn't support validating commands";
+
 pub trait Gamer: Sized {
     type PubState: Serialize + DeserializeOwned + Renderer;
     type PlayerState: Serialize + DeserializeOwned + Renderer;
+    /// Variants chosen when creating a game. Associated types can't have defaults yet, so games
+    /// without any need to declare `type Options = ();`.
+    type Options: Serialize + DeserializeOwned + Default;
 
     fn new(players: usize) -> Result<(Self, Vec<Log>), GameError>;
+    /// Create a new game whose randomness is derived from `seed`, games which use randomness
+    /// should override this and draw from a `GameRng` stored in the game.
+    fn new_with_seed(players: usize, _seed: u64) -> Result<(Self, Vec<Log>), GameError> {
+        Self::new(players)
+    }
+    /// Create a new game with options, games which have options should override this. Tooling
+    /// which creates games should call this, passing default options if none were chosen.
+    fn new_with_options(
+        players: usize,
+        _options: &Self::Options,
+        seed: u64,
+    ) -> Result<(Self, Vec<Log>), GameError> {
+        Self::new_with_seed(players, seed)
+    }
     fn pub_state(&self) -> Self::PubState;
     fn player_state(&self, player: usize) -> Self::PlayerState;
     fn command(
@@ -60,11 +84,36 @@ pub trait Gamer: Sized {
         input: &str,
         players: &[String],
     ) -> Result<CommandResponse, GameError>;
+    /// Find what `command` would do without changing the game, so a preview can be shown before
+    /// it is submitted. Unsupported by default, games which are `Clone` can implement it with
+    /// `validate_by_cloning`, others can check the command however is cheapest.
+    fn validate(
+        &self,
+        _player: usize,
+        _input: &str,
+        _players: &[String],
+    ) -> Result<CommandResponse, GameError> {
+        Err(GameError::internal(VALIDATE_UNSUPPORTED))
+    }
     fn status(&self) -> Status;
     fn command_spec(&self, player: usize) -> Option<command::Spec>;
     fn player_count(&self) -> usize;
     fn player_counts() -> Vec<usize>;
 
+    /// The spec for the input accepted by `parse_options`, so frontends can render an options
+    /// picker.
+    fn options_spec() -> Option<command::Spec> {
+        None
+    }
+
+    fn parse_options(input: &str) -> Result<Self::Options, GameError> {
+        if input.trim().is_empty() {
+            Ok(Self::Options::default())
+        } else {
+            Err(GameError::invalid_input("this game doesn't have any options"))
+        }
+    }
+
     fn is_finished(&self) -> bool {
         match self.status() {
             Status::Finished { .. } => true,
@@ -120,10 +169,249 @@ pub trait Gamer: Sized {
     }
 }
 
+/// Run a command on a clone of the game, for games implementing `Gamer::validate`.
+pub fn validate_by_cloning<G: Gamer + Clone>(
+    game: &G,
+    player: usize,
+    input: &str,
+    players: &[String],
+) -> Result<CommandResponse, GameError> {
+    game.clone().command(player, input, players)
+}
+
+/// Games which can list every legal command for a player, so bots don't need to guess. Games with
+/// a finite command spec can implement it with `enumerate_moves`.
+pub trait MoveEnumerator: Gamer {
+    fn legal_moves(&self, player: usize, players: &[String]) -> Vec<String>;
+}
+
+/// List the inputs accepted by the command spec which the game accepts when run on a clone. A
+/// clone is needed anyway, so this doesn't depend on the game implementing `validate`.
+/// Returns `None` if the spec accepts infinitely many or more than `MAX_ENUMERATED_MOVES` inputs.
+pub fn enumerate_moves<G: Gamer + Clone>(
+    game: &G,
+    player: usize,
+    players: &[String],
+) -> Option<Vec<String>> {
+    let inputs = match game.command_spec(player) {
+        Some(spec) => spec.enumerate(players, MAX_ENUMERATED_MOVES)?,
+        None => return Some(vec![]),
+    };
+    let mut seen: HashSet<String> = HashSet::new();
+    Some(
+        inputs
+            .into_iter()
+            .filter(|input| seen.insert(input.to_owned()))
+            .filter(|input| {
+                validate_by_cloning(game, player, input, players)
+                    .map(|response| response.remaining_input.trim().is_empty())
+                    .unwrap_or(false)
+            })
+            .collect(),
+    )
+}
+
 pub trait Renderer {
     fn render(&self) -> Vec<Node>;
 }
 
+/// A small, fast RNG for games to draw shuffles and dice from. Its state is serializable so a
+/// game which stores one will reproduce identical randomness after being serialized and
+/// deserialized.
+///
+/// Implements `rand::Rng`, so `shuffle`, `gen_range`, `choose` and friends are all available.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct GameRng {
+    state: u64,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Create an RNG with a random seed, for games created without one.
+    pub fn random() -> Self {
+        Self::from_seed(rand::thread_rng().gen())
+    }
+
+    /// Derive a seed for one of several consumers of randomness sharing `seed`, so each gets its
+    /// own stream instead of them all drawing identical values.
+    pub fn sub_seed(seed: u64, stream: u64) -> u64 {
+        Self::from_seed(seed ^ stream.wrapping_mul(0xBF58_476D_1CE4_E5B9)).next_u64()
+    }
+
+    /// Roll a die with the given number of sides, returning a value from 1 to `sides`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sides` is 0.
+    pub fn roll(&mut self, sides: u32) -> u32 {
+        assert!(sides > 0, "a die needs at least one side");
+        // Drawn as a u64 so a die with u32::MAX sides doesn't overflow.
+        self.gen_range(0, u64::from(sides)) as u32 + 1
+    }
+}
+
+impl Rng for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    // SplitMix64, see http://xoshiro.di.unimi.it/splitmix64.c
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+struct UndoStep {
+    player: usize,
+    state: serde_json::Value,
+    logs: Vec<Log>,
+}
+
+/// Wraps a game to allow players to undo commands which returned `can_undo`.
+///
+/// The state of the game is snapshotted before each command, and kept if the command can be
+/// undone. A player may undo a run of their own undoable commands, but only until another player
+/// makes a command or a command which can't be undone is made.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Undoable<G> {
+    pub game: G,
+    undo_steps: Vec<UndoStep>,
+}
+
+impl<G> Undoable<G>
+where
+    G: Gamer + Serialize + DeserializeOwned,
+{
+    pub fn from_game(game: G) -> Self {
+        Self {
+            game,
+            undo_steps: vec![],
+        }
+    }
+
+    pub fn can_undo(&self, player: usize) -> bool {
+        self.undo_steps
+            .last()
+            .map(|s| s.player == player)
+            .unwrap_or(false)
+    }
+
+    /// Undo the last command made by the player, returning the logs from that command so they can
+    /// be retracted.
+    pub fn undo(&mut self, player: usize) -> Result<Vec<Log>, GameError> {
+        if !self.can_undo(player) {
+            return Err(GameError::invalid_input("you can't undo"));
+        }
+        let step = self.undo_steps.pop().unwrap();
+        self.game = serde_json::from_value(step.state)
+            .map_err(|e| GameError::internal(format!("unable to restore game state: {}", e)))?;
+        Ok(step.logs)
+    }
+}
+
+impl<G> Gamer for Undoable<G>
+where
+    G: Gamer + Serialize + DeserializeOwned,
+{
+    type PubState = G::PubState;
+    type PlayerState = G::PlayerState;
+    type Options = G::Options;
+
+    fn new(players: usize) -> Result<(Self, Vec<Log>), GameError> {
+        G::new(players).map(|(game, logs)| (Self::from_game(game), logs))
+    }
+
+    fn new_with_seed(players: usize, seed: u64) -> Result<(Self, Vec<Log>), GameError> {
+        G::new_with_seed(players, seed).map(|(game, logs)| (Self::from_game(game), logs))
+    }
+
+    fn new_with_options(
+        players: usize,
+        options: &Self::Options,
+        seed: u64,
+    ) -> Result<(Self, Vec<Log>), GameError> {
+        G::new_with_options(players, options, seed)
+            .map(|(game, logs)| (Self::from_game(game), logs))
+    }
+
+    fn pub_state(&self) -> Self::PubState {
+        self.game.pub_state()
+    }
+
+    fn player_state(&self, player: usize) -> Self::PlayerState {
+        self.game.player_state(player)
+    }
+
+    fn command(
+        &mut self,
+        player: usize,
+        input: &str,
+        players: &[String],
+    ) -> Result<CommandResponse, GameError> {
+        let state = serde_json::to_value(&self.game)
+            .map_err(|e| GameError::internal(format!("unable to snapshot game state: {}", e)))?;
+        let response = self.game.command(player, input, players)?;
+        if !response.can_undo || self.game.is_finished() {
+            self.undo_steps.clear();
+        } else {
+            if !self.can_undo(player) {
+                self.undo_steps.clear();
+            }
+            self.undo_steps.push(UndoStep {
+                player,
+                state,
+                logs: response.logs.clone(),
+            });
+        }
+        Ok(response)
+    }
+
+    fn validate(
+        &self,
+        player: usize,
+        input: &str,
+        players: &[String],
+    ) -> Result<CommandResponse, GameError> {
+        self.game.validate(player, input, players)
+    }
+
+    fn status(&self) -> Status {
+        self.game.status()
+    }
+
+    fn command_spec(&self, player: usize) -> Option<command::Spec> {
+        self.game.command_spec(player)
+    }
+
+    fn player_count(&self) -> usize {
+        self.game.player_count()
+    }
+
+    fn player_counts() -> Vec<usize> {
+        G::player_counts()
+    }
+
+    fn options_spec() -> Option<command::Spec> {
+        G::options_spec()
+    }
+
+    fn parse_options(input: &str) -> Result<Self::Options, GameError> {
+        G::parse_options(input)
+    }
+
+    fn points(&self) -> Vec<f32> {
+        self.game.points()
+    }
+}
+
 fn cmp_fallback(a: &[i32], b: &[i32]) -> Ordering {
     if a.is_empty() && b.is_empty() {
         return Ordering::Equal;
@@ -169,6 +457,143 @@ pub fn gen_placings(metrics: &[Vec<i32>]) -> Vec<usize> {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::test_game::{BrokenRace, Race, RaceOptions};
+
+    #[test]
+    fn game_rng_is_deterministic() {
+        let mut a = GameRng::from_seed(1234);
+        let mut b = GameRng::from_seed(1234);
+        let mut deck: Vec<usize> = (0..52).collect();
+        a.shuffle(&mut deck);
+        let mut other_deck: Vec<usize> = (0..52).collect();
+        b.shuffle(&mut other_deck);
+        assert_eq!(deck, other_deck);
+
+        let mut restored: GameRng = serde_json::from_str(
+            &serde_json::to_string(&a).expect("expected GameRng to serialize"),
+        ).expect("expected GameRng to deserialize");
+        for _ in 0..100 {
+            let roll = a.roll(6);
+            assert!((1..=6).contains(&roll));
+            assert_eq!(roll, restored.roll(6));
+        }
+        assert_ne!(a.next_u64(), GameRng::from_seed(1235).next_u64());
+    }
+
+    #[test]
+    fn sub_seed_works() {
+        assert_eq!(GameRng::sub_seed(42, 1), GameRng::sub_seed(42, 1));
+        assert_ne!(GameRng::sub_seed(42, 1), GameRng::sub_seed(42, 2));
+        assert_ne!(
+            GameRng::from_seed(42).next_u64(),
+            GameRng::from_seed(GameRng::sub_seed(42, 1)).next_u64()
+        );
+    }
+
+    #[test]
+    fn new_with_seed_is_reproducible() {
+        let games = (0..20)
+            .map(|seed| Race::new_with_seed(4, seed).expect("expected game").0)
+            .collect::<Vec<Race>>();
+        for (seed, game) in games.iter().enumerate() {
+            let (again, _) = Race::new_with_seed(4, seed as u64).expect("expected game");
+            assert_eq!(game, &again);
+        }
+        assert!(games.iter().any(|g| g.current != games[0].current));
+    }
+
+    #[test]
+    fn roll_works() {
+        let mut rng = GameRng::from_seed(1);
+        assert_eq!(1, rng.roll(1));
+        for _ in 0..100 {
+            assert!(rng.roll(u32::MAX) >= 1);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "a die needs at least one side")]
+    fn roll_panics_without_sides() {
+        GameRng::from_seed(1).roll(0);
+    }
+
+    #[test]
+    fn options_work() {
+        let options = Race::parse_options("target 20").expect("expected options to parse");
+        let (game, _) = Race::new_with_options(2, &options, 0).expect("expected game");
+        assert_eq!(20, game.target);
+        let error = Race::parse_options("target 100").expect_err("expected target of 100 to fail");
+        assert!(error.to_string().contains("between 5 and 30"), "unexpected error: {}", error);
+        assert_eq!(
+            RaceOptions::default(),
+            Race::parse_options("").expect("expected empty options to parse")
+        );
+        assert_eq!(None, BrokenRace::options_spec());
+        BrokenRace::parse_options("target 20").expect_err("expected BrokenRace to have no options");
+    }
+
+    #[test]
+    fn undoable_works() {
+        let players = vec!["mick".to_string(), "steve".to_string()];
+        let (mut game, _) = Undoable::<Race>::new(2).expect("expected game");
+        assert!(!game.can_undo(0));
+        game.command(0, "add 1", &players).expect("expected command");
+        game.command(1, "add 2", &players).expect("expected command");
+        assert!(!game.can_undo(0));
+        assert!(game.can_undo(1));
+        game.undo(0).expect_err("expected player 0 to not be able to undo");
+        let logs = game.undo(1).expect("expected player 1 to undo");
+        assert_eq!(1, logs.len());
+        assert_eq!(1, game.game.total);
+        assert_eq!(vec![1], game.whose_turn());
+        assert!(!game.can_undo(1));
+        game.undo(1).expect_err("expected nothing left to undo");
+        game.command(1, "blah", &players)
+            .expect_err("expected invalid command");
+        game.command(1, "add 3", &players).expect("expected command");
+        game.command(1, "add 3", &players)
+            .expect_err("expected not your turn");
+        assert!(game.can_undo(1));
+        game.command(0, "add 3", &players).expect("expected command");
+        assert!(!game.can_undo(1));
+        assert!(game.can_undo(0));
+    }
+
+    #[test]
+    fn validate_works() {
+        let players = vec!["mick".to_string(), "steve".to_string()];
+        let (game, _) = Race::new(2).expect("expected game to start");
+        let response = game
+            .validate(0, "add 3 please", &players)
+            .expect("expected add 3 to be valid");
+        assert_eq!(1, response.logs.len());
+        assert_eq!(" please", response.remaining_input);
+        assert_eq!(0, game.total);
+        match game.validate(1, "add 3", &players) {
+            Err(GameError::NotYourTurn) => {}
+            r => panic!("expected not your turn error, got {:?}", r),
+        }
+        assert!(game.validate(0, "add 4", &players).is_err());
+        assert_eq!(Race::new(2).expect("expected game to start").0, game);
+        let (undoable, _) = Undoable::<Race>::new(2).expect("expected game to start");
+        undoable
+            .validate(0, "add 3", &players)
+            .expect("expected Undoable to validate with Race");
+        assert!(!undoable.can_undo(0));
+    }
+
+    #[test]
+    fn enumerate_moves_works() {
+        let players = vec!["mick".to_string(), "steve".to_string()];
+        let (game, _) = Race::new(2).expect("expected game to start");
+        assert_eq!(
+            Some(vec!["add 1".to_string(), "add 2".to_string(), "add 3".to_string()]),
+            enumerate_moves(&game, 0, &players)
+        );
+        assert_eq!(Some(vec![]), enumerate_moves(&game, 1, &players));
+    }
+
     #[test]
     fn gen_placings_works() {
         assert_eq!(
@@ -3,6 +3,16 @@ pub mod game_log;
 pub mod errors;
 pub mod command;
 pub mod bot;
+pub mod cli;
+pub mod protocol;
+pub mod rating;
+pub mod arena;
 
-pub use crate::game::{CommandResponse, Gamer, Renderer, Stat, Status};
+#[cfg(test)]
+mod test_game;
+
+pub use crate::game::{
+    enumerate_moves, validate_by_cloning, CommandResponse, GameRng, Gamer, MoveEnumerator,
+    Renderer, Stat, Status, Undoable,
+};
 pub use crate::game_log::Log;
@@ -0,0 +1,436 @@
+//! A line oriented JSON protocol for running a game engine as a separate process. Each line of
+//! input is a `Request` and each is answered with a single line containing a `Response`.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{self, Value};
+
+use brdgme_markup::Node;
+
+use std::io::{self, BufRead, Write};
+
+use crate::bot::{BotCommand, Botter, SpecBot};
+use crate::command::parser::{Parser, SpecValue};
+use crate::command::Spec as CommandSpec;
+use crate::errors::GameError;
+use crate::game::{Gamer, Renderer, Status, VALIDATE_UNSUPPORTED};
+use crate::game_log::Log;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Request {
+    PlayerCounts,
+    New {
+        players: usize,
+        options: Option<Value>,
+        seed: u64,
+    },
+    Status {
+        game: Value,
+    },
+    Command {
+        player: usize,
+        command: String,
+        names: Vec<String>,
+        game: Value,
+    },
+    Validate {
+        player: usize,
+        command: String,
+        names: Vec<String>,
+        game: Value,
+    },
+    PlayerRender {
+        player: usize,
+        game: Value,
+    },
+    PubRender {
+        game: Value,
+    },
+    Spec {
+        player: usize,
+        game: Value,
+    },
+    Bot {
+        player: usize,
+        names: Vec<String>,
+        game: Value,
+        game_id: Option<String>,
+    },
+}
+
+/// The serialized state of a game along with what the server needs to know about it.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct GameResponse {
+    pub state: Value,
+    pub status: Status,
+    pub points: Vec<f32>,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Render {
+    pub state: Value,
+    pub render: Vec<Node>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Response {
+    PlayerCounts {
+        player_counts: Vec<usize>,
+    },
+    New {
+        game: GameResponse,
+        logs: Vec<Log>,
+    },
+    Status {
+        game: GameResponse,
+    },
+    Command {
+        game: GameResponse,
+        logs: Vec<Log>,
+        can_undo: bool,
+        remaining_input: String,
+    },
+    Validate {
+        logs: Vec<Log>,
+        can_undo: bool,
+        remaining_input: String,
+        /// The command parsed with the player's command spec.
+        parsed: Option<SpecValue>,
+    },
+    PlayerRender {
+        render: Render,
+    },
+    PubRender {
+        render: Render,
+    },
+    Spec {
+        spec: Option<CommandSpec>,
+    },
+    Bot {
+        commands: Vec<BotCommand>,
+    },
+    Error {
+        error: GameError,
+    },
+}
+
+/// Run the protocol over stdin and stdout, using a `SpecBot` to answer bot requests.
+pub fn run_stdio<G>() -> io::Result<()>
+where
+    G: Gamer + Serialize + DeserializeOwned,
+{
+    run_stdio_with_bot::<G, _>(&mut SpecBot::new())
+}
+
+pub fn run_stdio_with_bot<G, B>(bot: &mut B) -> io::Result<()>
+where
+    G: Gamer + Serialize + DeserializeOwned,
+    B: Botter<G>,
+{
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    run::<G, B, _, _>(stdin.lock(), &mut stdout.lock(), bot)
+}
+
+/// Answer each request line from `input` with a response line on `output`.
+pub fn run<G, B, I, O>(input: I, output: &mut O, bot: &mut B) -> io::Result<()>
+where
+    G: Gamer + Serialize + DeserializeOwned,
+    B: Botter<G>,
+    I: BufRead,
+    O: Write,
+{
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str(&line) {
+            Ok(request) => handle::<G, B>(request, bot),
+            Err(e) => Response::Error {
+                error: GameError::internal(format!("unable to parse request: {}", e)),
+            },
+        };
+        serde_json::to_writer(&mut *output, &response)?;
+        writeln!(output)?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+pub fn handle<G, B>(request: Request, bot: &mut B) -> Response
+where
+    G: Gamer + Serialize + DeserializeOwned,
+    B: Botter<G>,
+{
+    match handle_request::<G, B>(request, bot) {
+        Ok(response) => response,
+        Err(error) => Response::Error { error },
+    }
+}
+
+fn handle_request<G, B>(request: Request, bot: &mut B) -> Result<Response, GameError>
+where
+    G: Gamer + Serialize + DeserializeOwned,
+    B: Botter<G>,
+{
+    match request {
+        Request::PlayerCounts => Ok(Response::PlayerCounts {
+            player_counts: G::player_counts(),
+        }),
+        Request::New {
+            players,
+            options,
+            seed,
+        } => {
+            let options: G::Options = match options {
+                Some(options) => from_value(options, "options")?,
+                None => G::Options::default(),
+            };
+            let (game, logs) = G::new_with_options(players, &options, seed)?;
+            Ok(Response::New {
+                game: game_response(&game)?,
+                logs,
+            })
+        }
+        Request::Status { game } => {
+            let game: G = from_value(game, "game")?;
+            Ok(Response::Status {
+                game: game_response(&game)?,
+            })
+        }
+        Request::Command {
+            player,
+            command,
+            names,
+            game,
+        } => {
+            let mut game: G = from_value(game, "game")?;
+            let response = game.command(player, &command, &names)?;
+            Ok(Response::Command {
+                game: game_response(&game)?,
+                logs: response.logs,
+                can_undo: response.can_undo,
+                remaining_input: response.remaining_input,
+            })
+        }
+        Request::Validate {
+            player,
+            command,
+            names,
+            game,
+        } => {
+            let mut game: G = from_value(game, "game")?;
+            let parsed = game
+                .command_spec(player)
+                .and_then(|spec| spec.parse(&command, &names).ok())
+                .map(|output| output.value);
+            let response = match game.validate(player, &command, &names) {
+                // The game is a copy which is thrown away, so running the command is safe.
+                Err(GameError::Internal { ref message }) if message == VALIDATE_UNSUPPORTED => {
+                    game.command(player, &command, &names)?
+                }
+                result => result?,
+            };
+            Ok(Response::Validate {
+                logs: response.logs,
+                can_undo: response.can_undo,
+                remaining_input: response.remaining_input,
+                parsed,
+            })
+        }
+        Request::PlayerRender { player, game } => {
+            let game: G = from_value(game, "game")?;
+            let state = game.player_state(player);
+            Ok(Response::PlayerRender {
+                render: Render {
+                    render: state.render(),
+                    state: to_value(&state, "player state")?,
+                },
+            })
+        }
+        Request::PubRender { game } => {
+            let game: G = from_value(game, "game")?;
+            let state = game.pub_state();
+            Ok(Response::PubRender {
+                render: Render {
+                    render: state.render(),
+                    state: to_value(&state, "public state")?,
+                },
+            })
+        }
+        Request::Spec { player, game } => {
+            let game: G = from_value(game, "game")?;
+            Ok(Response::Spec {
+                spec: game.command_spec(player),
+            })
+        }
+        Request::Bot {
+            player,
+            names,
+            game,
+            game_id,
+        } => {
+            let game: G = from_value(game, "game")?;
+            let spec = game
+                .command_spec(player)
+                .ok_or(GameError::NotYourTurn)?;
+            Ok(Response::Bot {
+                commands: bot.commands(player, &game.player_state(player), &names, &spec, game_id),
+            })
+        }
+    }
+}
+
+fn game_response<G: Gamer + Serialize>(game: &G) -> Result<GameResponse, GameError> {
+    Ok(GameResponse {
+        state: to_value(game, "game")?,
+        status: game.status(),
+        points: game.points(),
+    })
+}
+
+fn to_value<T: Serialize>(value: &T, name: &str) -> Result<Value, GameError> {
+    serde_json::to_value(value)
+        .map_err(|e| GameError::internal(format!("unable to serialize {}: {}", name, e)))
+}
+
+fn from_value<T: DeserializeOwned>(value: Value, name: &str) -> Result<T, GameError> {
+    serde_json::from_value(value)
+        .map_err(|e| GameError::internal(format!("unable to deserialize {}: {}", name, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_game::{BrokenRace, Race};
+
+    fn request(request: &Request) -> Response {
+        request_for::<Race>(request)
+    }
+
+    fn request_for<G: Gamer + Serialize + DeserializeOwned>(request: &Request) -> Response {
+        let input = serde_json::to_string(request).expect("expected request to serialize");
+        let mut output: Vec<u8> = vec![];
+        run::<G, _, _, _>(input.as_bytes(), &mut output, &mut SpecBot::new())
+            .expect("expected run to succeed");
+        serde_json::from_slice(&output).expect("expected response to deserialize")
+    }
+
+    #[test]
+    fn run_works() {
+        let names = vec!["mick".to_string(), "steve".to_string()];
+        let game = match request(&Request::New {
+            players: 2,
+            options: Some(json_value(r#"{"target": 20}"#)),
+            seed: 0,
+        }) {
+            Response::New { game, .. } => game,
+            r => panic!("expected new response, got {:?}", r),
+        };
+        assert_eq!(Some(&json_value("20")), game.state.get("target"));
+        let game = match request(&Request::Command {
+            player: 0,
+            command: "add 2".to_string(),
+            names: names.clone(),
+            game: game.state,
+        }) {
+            Response::Command { game, logs, .. } => {
+                assert_eq!(1, logs.len());
+                game
+            }
+            r => panic!("expected command response, got {:?}", r),
+        };
+        assert_eq!(
+            Status::Active {
+                whose_turn: vec![1],
+                eliminated: vec![],
+            },
+            game.status
+        );
+        match request(&Request::Validate {
+            player: 1,
+            command: "add 3".to_string(),
+            names: names.clone(),
+            game: game.state.clone(),
+        }) {
+            Response::Validate { logs, parsed, .. } => {
+                assert_eq!(1, logs.len());
+                assert_eq!(
+                    Some(SpecValue::Chain(vec![
+                        SpecValue::Token("add".to_string()),
+                        SpecValue::Chain(vec![
+                            SpecValue::Space(" ".to_string()),
+                            SpecValue::Int(3),
+                        ]),
+                    ])),
+                    parsed
+                );
+            }
+            r => panic!("expected validate response, got {:?}", r),
+        }
+        match request(&Request::Command {
+            player: 0,
+            command: "add 2".to_string(),
+            names: names.clone(),
+            game: game.state.clone(),
+        }) {
+            Response::Error {
+                error: GameError::NotYourTurn,
+            } => {}
+            r => panic!("expected not your turn error, got {:?}", r),
+        }
+        match request(&Request::Bot {
+            player: 1,
+            names,
+            game: game.state,
+            game_id: None,
+        }) {
+            Response::Bot { commands } => assert_eq!(1, commands.len()),
+            r => panic!("expected bot response, got {:?}", r),
+        }
+        match request(&Request::PlayerCounts) {
+            Response::PlayerCounts { player_counts } => assert_eq!(vec![2, 3, 4], player_counts),
+            r => panic!("expected player counts response, got {:?}", r),
+        }
+    }
+
+    #[test]
+    fn validate_falls_back_to_command() {
+        let game = match request_for::<BrokenRace>(&Request::New {
+            players: 2,
+            options: None,
+            seed: 0,
+        }) {
+            Response::New { game, .. } => game,
+            r => panic!("expected new response, got {:?}", r),
+        };
+        match request_for::<BrokenRace>(&Request::Validate {
+            player: 0,
+            command: "add 3".to_string(),
+            names: vec!["mick".to_string(), "steve".to_string()],
+            game: game.state,
+        }) {
+            Response::Validate { logs, .. } => assert_eq!(1, logs.len()),
+            r => panic!("expected validate response, got {:?}", r),
+        }
+    }
+
+    #[test]
+    fn run_handles_bad_input() {
+        let mut output: Vec<u8> = vec![];
+        run::<Race, _, _, _>("\nfart\n".as_bytes(), &mut output, &mut SpecBot::new())
+            .expect("expected run to succeed");
+        match serde_json::from_slice(&output).expect("expected response to deserialize") {
+            Response::Error {
+                error: GameError::Internal { .. },
+            } => {}
+            r => panic!("expected internal error, got {:?}", r),
+        }
+    }
+
+    fn json_value(input: &str) -> Value {
+        serde_json::from_str(input).expect("expected valid JSON")
+    }
+}
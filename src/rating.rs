@@ -0,0 +1,361 @@
+//! Multiplayer rating updates from finished games. Every game is treated as a set of pairwise
+//! results between all players, a player beating everyone placed below them and drawing with
+//! everyone sharing their placing. Eliminated players are rated by the placing they were given.
+
+use serde_derive::{Deserialize, Serialize};
+
+use std::f64::consts::PI;
+
+use crate::errors::GameError;
+use crate::game::Status;
+
+/// Ratings are stored and reported on the Glicko scale, which is the same as Elo.
+pub const DEFAULT_RATING: f64 = 1500.0;
+const GLICKO2_SCALE: f64 = 173.7178;
+const GLICKO2_EPSILON: f64 = 0.000_001;
+// Bounds both volatility loops, which converge far sooner for ratings that aren't degenerate.
+const GLICKO2_MAX_ITERATIONS: usize = 1000;
+
+pub trait Rater {
+    type Rating;
+
+    /// Update `ratings` with the result of a game, where `placings[p]` is the placing of player
+    /// `p` starting from 1, and equal placings are ties.
+    fn rate(
+        &self,
+        ratings: &[Self::Rating],
+        placings: &[usize],
+    ) -> Result<Vec<Self::Rating>, GameError>;
+
+    fn rate_status(
+        &self,
+        ratings: &[Self::Rating],
+        status: &Status,
+    ) -> Result<Vec<Self::Rating>, GameError> {
+        match *status {
+            Status::Finished { ref placings, .. } => self.rate(ratings, placings),
+            Status::Active { .. } => Err(GameError::internal("can't rate an active game")),
+        }
+    }
+}
+
+/// The score of a player placed at `a` against a player placed at `b`.
+fn score(a: usize, b: usize) -> f64 {
+    if a < b {
+        1.0
+    } else if a == b {
+        0.5
+    } else {
+        0.0
+    }
+}
+
+fn check_placings(rating_count: usize, placings: &[usize]) -> Result<(), GameError> {
+    if rating_count != placings.len() {
+        return Err(GameError::internal(format!(
+            "got {} ratings but {} placings",
+            rating_count,
+            placings.len()
+        )));
+    }
+    if placings.iter().any(|&p| p == 0 || p > placings.len()) {
+        return Err(GameError::internal(format!(
+            "placings must be between 1 and {}",
+            placings.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Pairwise Elo, with `k` split between all opponents so a game is worth the same amount
+/// regardless of player count.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Elo {
+    pub k: f64,
+}
+
+impl Elo {
+    pub fn new(k: f64) -> Self {
+        Self { k }
+    }
+
+    /// The expected score of a player rated `a` against a player rated `b`.
+    pub fn expected(a: f64, b: f64) -> f64 {
+        1.0 / (1.0 + 10f64.powf((b - a) / 400.0))
+    }
+}
+
+impl Default for Elo {
+    fn default() -> Self {
+        Self::new(32.0)
+    }
+}
+
+impl Rater for Elo {
+    type Rating = f64;
+
+    fn rate(&self, ratings: &[f64], placings: &[usize]) -> Result<Vec<f64>, GameError> {
+        check_placings(ratings.len(), placings)?;
+        if ratings.len() < 2 {
+            return Ok(ratings.to_vec());
+        }
+        let k = self.k / (ratings.len() - 1) as f64;
+        Ok(ratings
+            .iter()
+            .enumerate()
+            .map(|(p, &rating)| {
+                rating + k * ratings
+                    .iter()
+                    .enumerate()
+                    .filter(|&(opp, _)| opp != p)
+                    .map(|(opp, &opp_rating)| {
+                        score(placings[p], placings[opp]) - Elo::expected(rating, opp_rating)
+                    })
+                    .sum::<f64>()
+            })
+            .collect())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GlickoRating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+impl Default for GlickoRating {
+    fn default() -> Self {
+        Self {
+            rating: DEFAULT_RATING,
+            deviation: 350.0,
+            volatility: 0.06,
+        }
+    }
+}
+
+/// Glicko-2, treating each game as a rating period in which the player played every opponent.
+/// `tau` constrains how quickly volatility changes, sensible values are between 0.3 and 1.2. It
+/// must be positive, rating fails otherwise.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Glicko2 {
+    pub tau: f64,
+}
+
+impl Glicko2 {
+    pub fn new(tau: f64) -> Self {
+        Self { tau }
+    }
+
+    /// Update `rating` for a period with results against `opponents`, each paired with the score
+    /// against them.
+    fn update(&self, rating: &GlickoRating, opponents: &[(&GlickoRating, f64)]) -> GlickoRating {
+        let mu = (rating.rating - DEFAULT_RATING) / GLICKO2_SCALE;
+        let phi = rating.deviation / GLICKO2_SCALE;
+        if opponents.is_empty() {
+            let phi_star = (phi.powi(2) + rating.volatility.powi(2)).sqrt();
+            return GlickoRating {
+                deviation: phi_star * GLICKO2_SCALE,
+                ..rating.clone()
+            };
+        }
+        let results = opponents
+            .iter()
+            .map(|&(opp, s)| {
+                let opp_mu = (opp.rating - DEFAULT_RATING) / GLICKO2_SCALE;
+                let g = glicko2_g(opp.deviation / GLICKO2_SCALE);
+                (g, 1.0 / (1.0 + (-g * (mu - opp_mu)).exp()), s)
+            })
+            .collect::<Vec<(f64, f64, f64)>>();
+        let v = 1.0 / results
+            .iter()
+            .map(|&(g, e, _)| g.powi(2) * e * (1.0 - e))
+            .sum::<f64>();
+        let improvement = results.iter().map(|&(g, e, s)| g * (s - e)).sum::<f64>();
+        let volatility = self.volatility(phi, rating.volatility, v, v * improvement);
+        let phi_star = (phi.powi(2) + volatility.powi(2)).sqrt();
+        let new_phi = 1.0 / (1.0 / phi_star.powi(2) + 1.0 / v).sqrt();
+        GlickoRating {
+            rating: (mu + new_phi.powi(2) * improvement) * GLICKO2_SCALE + DEFAULT_RATING,
+            deviation: new_phi * GLICKO2_SCALE,
+            volatility,
+        }
+    }
+
+    /// Find the new volatility using the Illinois algorithm.
+    fn volatility(&self, phi: f64, sigma: f64, v: f64, delta: f64) -> f64 {
+        let a = sigma.powi(2).ln();
+        let f = |x: f64| {
+            let ex = x.exp();
+            ex * (delta.powi(2) - phi.powi(2) - v - ex)
+                / (2.0 * (phi.powi(2) + v + ex).powi(2))
+                - (x - a) / self.tau.powi(2)
+        };
+        let mut big_a = a;
+        let mut big_b = if delta.powi(2) > phi.powi(2) + v {
+            (delta.powi(2) - phi.powi(2) - v).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * self.tau) < 0.0 && k < GLICKO2_MAX_ITERATIONS as f64 {
+                k += 1.0;
+            }
+            a - k * self.tau
+        };
+        let mut f_a = f(big_a);
+        let mut f_b = f(big_b);
+        let mut iterations = 0;
+        while (big_b - big_a).abs() > GLICKO2_EPSILON && iterations < GLICKO2_MAX_ITERATIONS {
+            iterations += 1;
+            let big_c = big_a + (big_a - big_b) * f_a / (f_b - f_a);
+            let f_c = f(big_c);
+            if f_c * f_b <= 0.0 {
+                big_a = big_b;
+                f_a = f_b;
+            } else {
+                f_a /= 2.0;
+            }
+            big_b = big_c;
+            f_b = f_c;
+        }
+        (big_a / 2.0).exp()
+    }
+}
+
+fn glicko2_g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi.powi(2) / PI.powi(2)).sqrt()
+}
+
+impl Default for Glicko2 {
+    fn default() -> Self {
+        Self::new(0.5)
+    }
+}
+
+impl Rater for Glicko2 {
+    type Rating = GlickoRating;
+
+    fn rate(
+        &self,
+        ratings: &[GlickoRating],
+        placings: &[usize],
+    ) -> Result<Vec<GlickoRating>, GameError> {
+        check_placings(ratings.len(), placings)?;
+        if self.tau.is_nan() || self.tau <= 0.0 {
+            return Err(GameError::internal(format!("tau must be positive, got {}", self.tau)));
+        }
+        Ok(ratings
+            .iter()
+            .enumerate()
+            .map(|(p, rating)| {
+                let opponents = ratings
+                    .iter()
+                    .enumerate()
+                    .filter(|&(opp, _)| opp != p)
+                    .map(|(opp, opp_rating)| (opp_rating, score(placings[p], placings[opp])))
+                    .collect::<Vec<(&GlickoRating, f64)>>();
+                self.update(rating, &opponents)
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(expected: f64, actual: f64, tolerance: f64) {
+        assert!(
+            (expected - actual).abs() < tolerance,
+            "expected {} but got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn elo_works() {
+        let elo = Elo::default();
+        let rated = elo.rate(&[1500.0, 1500.0], &[2, 1]).expect("expected rating to succeed");
+        assert_close(1484.0, rated[0], 0.001);
+        assert_close(1516.0, rated[1], 0.001);
+        // Ties between equal players don't change anything.
+        assert_eq!(
+            vec![1500.0, 1500.0, 1500.0],
+            elo.rate(&[1500.0, 1500.0, 1500.0], &[1, 1, 1]).expect("expected rating to succeed")
+        );
+        // Two eliminated players sharing last place, total rating is conserved.
+        let ratings = [1600.0, 1450.0, 1500.0, 1520.0];
+        let rated = elo.rate(&ratings, &[3, 1, 3, 2]).expect("expected rating to succeed");
+        assert_close(ratings.iter().sum(), rated.iter().sum(), 0.001);
+        assert!(rated[0] < ratings[0]);
+        assert!(rated[1] > ratings[1]);
+        assert!(rated[0] - ratings[0] < rated[2] - ratings[2]);
+        assert!(elo.rate(&ratings, &[1, 2]).is_err());
+        assert!(elo.rate(&ratings, &[1, 2, 3, 5]).is_err());
+    }
+
+    #[test]
+    fn glicko2_update_works() {
+        // The example from the Glicko-2 paper.
+        let opponent = |rating, deviation| GlickoRating {
+            rating,
+            deviation,
+            volatility: 0.06,
+        };
+        let rated = Glicko2::default().update(
+            &opponent(1500.0, 200.0),
+            &[
+                (&opponent(1400.0, 30.0), 1.0),
+                (&opponent(1550.0, 100.0), 0.0),
+                (&opponent(1700.0, 300.0), 0.0),
+            ],
+        );
+        assert_close(1464.06, rated.rating, 0.01);
+        assert_close(151.52, rated.deviation, 0.01);
+        assert_close(0.05999, rated.volatility, 0.00001);
+    }
+
+    #[test]
+    fn glicko2_rejects_bad_tau() {
+        let ratings = vec![GlickoRating::default(); 2];
+        for &tau in &[0.0, -0.5, f64::NAN] {
+            assert!(Glicko2::new(tau).rate(&ratings, &[1, 2]).is_err());
+        }
+        // Degenerate ratings stop rather than looping forever.
+        let nan = GlickoRating {
+            volatility: f64::NAN,
+            ..GlickoRating::default()
+        };
+        Glicko2::default()
+            .rate(&[nan, GlickoRating::default()], &[1, 2])
+            .expect("expected rating to finish");
+    }
+
+    #[test]
+    fn rate_status_works() {
+        let glicko = Glicko2::default();
+        let ratings = vec![GlickoRating::default(); 3];
+        let rated = glicko
+            .rate_status(
+                &ratings,
+                &Status::Finished {
+                    placings: vec![2, 1, 2],
+                    stats: vec![],
+                },
+            )
+            .expect("expected rating to succeed");
+        assert!(rated[1].rating > DEFAULT_RATING);
+        assert_close(rated[0].rating, rated[2].rating, 0.001);
+        assert!(rated[0].rating < DEFAULT_RATING);
+        assert!(rated.iter().all(|r| r.deviation < 350.0));
+        assert!(glicko
+            .rate_status(
+                &ratings,
+                &Status::Active {
+                    whose_turn: vec![0],
+                    eliminated: vec![],
+                },
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,259 @@
+//! A tiny game used to exercise the game agnostic tooling in tests.
+
+use serde_derive::{Deserialize, Serialize};
+
+use brdgme_markup::Node;
+
+use std::collections::HashMap;
+
+use crate::command;
+use crate::command::parser::*;
+use crate::errors::GameError;
+use crate::game::{
+    enumerate_moves, gen_placings, validate_by_cloning, CommandResponse, Gamer, MoveEnumerator,
+    Renderer, Status,
+};
+use crate::game_log::Log;
+
+pub const TARGET: i32 = 10;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RaceOptions {
+    pub target: i32,
+}
+
+impl Default for RaceOptions {
+    fn default() -> Self {
+        RaceOptions { target: TARGET }
+    }
+}
+
+fn options_parser() -> impl Parser<RaceOptions> {
+    Map::new(
+        Chain2::new(Token::new("target"), AfterSpace::new(Int::bounded(5, 30))),
+        |(_, target)| RaceOptions { target },
+    )
+}
+
+/// Players take turns adding 1 to 3 to a running total, the player who reaches the target wins.
+/// The seed picks the starting player, so seed 0 always starts with the first player.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Race {
+    pub players: usize,
+    pub target: i32,
+    pub current: usize,
+    pub total: i32,
+    pub added: Vec<i32>,
+    pub winner: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RaceState {
+    pub total: i32,
+    pub current: usize,
+}
+
+impl Renderer for RaceState {
+    fn render(&self) -> Vec<Node> {
+        vec![Node::text(format!("total: {}", self.total))]
+    }
+}
+
+impl Gamer for Race {
+    type PubState = RaceState;
+    type PlayerState = RaceState;
+    type Options = RaceOptions;
+
+    fn new(players: usize) -> Result<(Self, Vec<Log>), GameError> {
+        Self::new_with_seed(players, 0)
+    }
+
+    fn new_with_seed(players: usize, seed: u64) -> Result<(Self, Vec<Log>), GameError> {
+        Self::new_with_options(players, &RaceOptions::default(), seed)
+    }
+
+    fn new_with_options(
+        players: usize,
+        options: &RaceOptions,
+        seed: u64,
+    ) -> Result<(Self, Vec<Log>), GameError> {
+        if !(2..=4).contains(&players) {
+            return Err(GameError::PlayerCount {
+                min: 2,
+                max: 4,
+                given: players,
+            });
+        }
+        Ok((
+            Race {
+                players,
+                target: options.target,
+                current: (seed % players as u64) as usize,
+                total: 0,
+                added: vec![0; players],
+                winner: None,
+            },
+            vec![],
+        ))
+    }
+
+    fn pub_state(&self) -> RaceState {
+        RaceState {
+            total: self.total,
+            current: self.current,
+        }
+    }
+
+    fn player_state(&self, _player: usize) -> RaceState {
+        self.pub_state()
+    }
+
+    fn command(
+        &mut self,
+        player: usize,
+        input: &str,
+        _players: &[String],
+    ) -> Result<CommandResponse, GameError> {
+        self.assert_not_finished()?;
+        self.assert_player_turn(player)?;
+        let output = Chain2::new(Token::new("add"), AfterSpace::new(Int::bounded(1, 3)))
+            .parse(input, &[])
+            .map_err(|e| GameError::invalid_input(e.to_string()))?;
+        let amount = (output.value).1;
+        self.total += amount;
+        self.added[player] += amount;
+        if self.total >= self.target {
+            self.winner = Some(player);
+        } else {
+            self.current = (self.current + 1) % self.players;
+        }
+        Ok(CommandResponse {
+            logs: vec![Log::public(vec![
+                Node::Player(player),
+                Node::text(format!(" added {}", amount)),
+            ])],
+            can_undo: true,
+            remaining_input: output.remaining.to_string(),
+        })
+    }
+
+    fn validate(
+        &self,
+        player: usize,
+        input: &str,
+        players: &[String],
+    ) -> Result<CommandResponse, GameError> {
+        validate_by_cloning(self, player, input, players)
+    }
+
+    fn status(&self) -> Status {
+        match self.winner {
+            Some(winner) => Status::Finished {
+                placings: gen_placings(
+                    &(0..self.players)
+                        .map(|p| vec![if p == winner { 1 } else { 0 }])
+                        .collect::<Vec<Vec<i32>>>(),
+                ),
+                stats: vec![HashMap::new(); self.players],
+            },
+            None => Status::Active {
+                whose_turn: vec![self.current],
+                eliminated: vec![],
+            },
+        }
+    }
+
+    fn command_spec(&self, _player: usize) -> Option<command::Spec> {
+        Some(Chain2::new(Token::new("add"), AfterSpace::new(Int::bounded(1, 3))).to_spec())
+    }
+
+    fn player_count(&self) -> usize {
+        self.players
+    }
+
+    fn player_counts() -> Vec<usize> {
+        (2..5).collect()
+    }
+
+    fn options_spec() -> Option<command::Spec> {
+        Some(options_parser().to_spec())
+    }
+
+    fn parse_options(input: &str) -> Result<RaceOptions, GameError> {
+        if input.trim().is_empty() {
+            return Ok(RaceOptions::default());
+        }
+        let output = options_parser().parse(input, &[])?;
+        if !output.remaining.trim().is_empty() {
+            return Err(GameError::invalid_input(format!(
+                "unexpected '{}'",
+                output.remaining
+            )));
+        }
+        Ok(output.value)
+    }
+
+    fn points(&self) -> Vec<f32> {
+        self.added.iter().map(|&a| a as f32).collect()
+    }
+}
+
+impl MoveEnumerator for Race {
+    fn legal_moves(&self, player: usize, players: &[String]) -> Vec<String> {
+        enumerate_moves(self, player, players).unwrap_or_default()
+    }
+}
+
+/// The total at which `BrokenRace` returns an internal error.
+pub const BROKEN_AT: i32 = 7;
+
+/// A `Race` with a bug which is triggered when the total hits `BROKEN_AT`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BrokenRace(pub Race);
+
+impl Gamer for BrokenRace {
+    type PubState = RaceState;
+    type PlayerState = RaceState;
+    type Options = ();
+
+    fn new(players: usize) -> Result<(Self, Vec<Log>), GameError> {
+        Race::new(players).map(|(race, logs)| (BrokenRace(race), logs))
+    }
+
+    fn pub_state(&self) -> RaceState {
+        self.0.pub_state()
+    }
+
+    fn player_state(&self, player: usize) -> RaceState {
+        self.0.player_state(player)
+    }
+
+    fn command(
+        &mut self,
+        player: usize,
+        input: &str,
+        players: &[String],
+    ) -> Result<CommandResponse, GameError> {
+        let response = self.0.command(player, input, players)?;
+        if self.0.total == BROKEN_AT {
+            return Err(GameError::internal("total is broken"));
+        }
+        Ok(response)
+    }
+
+    fn status(&self) -> Status {
+        self.0.status()
+    }
+
+    fn command_spec(&self, player: usize) -> Option<command::Spec> {
+        self.0.command_spec(player)
+    }
+
+    fn player_count(&self) -> usize {
+        self.0.player_count()
+    }
+
+    fn player_counts() -> Vec<usize> {
+        Race::player_counts()
+    }
+}